@@ -0,0 +1,136 @@
+use std::{alloc::{GlobalAlloc, Layout, System}, sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed}};
+
+/// Heap usage of a measured piece of code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AllocStats {
+    /// Number of allocations, including reallocations
+    pub allocations: usize,
+    /// Total number of bytes allocated
+    pub bytes: usize,
+    /// Highest number of live bytes above the level at the start
+    pub peak: usize,
+}
+
+/// Global allocator that can count allocations
+///
+/// Counting is disabled until `enable` is called, so installing it costs only
+/// a check of a flag on each allocation. Counters are shared by all threads.
+pub struct CountingAllocator {
+    enabled: AtomicBool,
+    allocations: AtomicUsize,
+    bytes: AtomicUsize,
+    live: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            allocations: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            live: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Relaxed)
+    }
+
+    /// Call `f` and count its allocations, `None` if counting is disabled
+    pub fn measure<T>(&self, f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+        if !self.is_enabled() {
+            return (f(), None);
+        }
+        let allocations = self.allocations.load(Relaxed);
+        let bytes = self.bytes.load(Relaxed);
+        let live = self.live.load(Relaxed);
+        self.peak.store(live, Relaxed);
+
+        let result = f();
+
+        let stats = AllocStats {
+            allocations: self.allocations.load(Relaxed) - allocations,
+            bytes: self.bytes.load(Relaxed) - bytes,
+            peak: self.peak.load(Relaxed).saturating_sub(live),
+        };
+        (result, Some(stats))
+    }
+
+    fn allocated(&self, size: usize) {
+        self.allocations.fetch_add(1, Relaxed);
+        self.bytes.fetch_add(size, Relaxed);
+        let live = self.live.fetch_add(size, Relaxed) + size;
+        self.peak.fetch_max(live, Relaxed);
+    }
+
+    fn freed(&self, size: usize) {
+        // memory allocated before enabling is not counted as live
+        let _ = self.live.fetch_update(Relaxed, Relaxed, |live| Some(live.saturating_sub(size)));
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if self.is_enabled() {
+            self.allocated(layout.size());
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if self.is_enabled() {
+            self.allocated(layout.size());
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if self.is_enabled() {
+            self.freed(layout.size());
+        }
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if self.is_enabled() {
+            self.freed(layout.size());
+            self.allocated(new_size);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled() {
+        let counter = CountingAllocator::new();
+        assert_eq!(counter.measure(|| 1), (1, None));
+    }
+
+    #[test]
+    fn counting() {
+        let counter = CountingAllocator::new();
+        counter.enable();
+        let (_, stats) = counter.measure(|| {
+            counter.allocated(100);
+            counter.freed(100);
+            counter.allocated(50);
+        });
+        assert_eq!(stats, Some(AllocStats { allocations: 2, bytes: 150, peak: 100 }));
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+/// Summary statistics of repeated time measurements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Calculate statistics of given samples, `None` if there are no samples
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let n = sorted.len();
+        let median = if n.is_multiple_of(2) {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+
+        let secs: Vec<_> = sorted.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let variance = secs.iter()
+            .map(|s| (s - mean).powi(2))
+            .sum::<f64>() / n as f64;
+
+        Some(Self {
+            runs: n,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// Run `f` one time for warm-up and then `runs` times measuring each call
+///
+/// Returns the result of the last call together with the measured times,
+/// or the first error, so a failing warm-up is not repeated.
+pub fn measure<T, E, F: FnMut() -> Result<T, E>>(runs: usize, mut f: F) -> Result<(T, Vec<Duration>), E> {
+    let mut result = f()?;
+    let mut samples = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let start = Instant::now();
+        result = f()?;
+        samples.push(start.elapsed());
+    }
+    Ok((result, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(v: &[u64]) -> Vec<Duration> {
+        v.iter().map(|ms| Duration::from_millis(*ms)).collect()
+    }
+
+    #[test]
+    fn stats_odd() {
+        let stats = Stats::from_samples(&ms(&[5, 1, 3])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(stats.mean.as_micros(), 3000);
+    }
+
+    #[test]
+    fn stats_even() {
+        let stats = Stats::from_samples(&ms(&[2, 4, 4, 4, 5, 5, 7, 9])).unwrap();
+        assert_eq!(stats.median, Duration::from_micros(4500));
+        assert_eq!(stats.mean.as_micros(), 5000);
+        assert_eq!(stats.stddev.as_micros(), 2000);
+    }
+
+    #[test]
+    fn stats_empty() {
+        assert!(Stats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn measure_excludes_warmup() {
+        let mut calls = 0;
+        let (last, samples) = measure(3, || { calls += 1; Ok::<_, ()>(calls) }).unwrap();
+        assert_eq!(last, 4);
+        assert_eq!(samples.len(), 3);
+    }
+
+    #[test]
+    fn measure_stops_at_error() {
+        let mut calls = 0;
+        assert_eq!(measure(3, || { calls += 1; Err::<(), _>(calls) }), Err(1));
+    }
+}
@@ -0,0 +1,216 @@
+use std::{str::FromStr, fmt::Display, fs, path::{Path, PathBuf}, collections::BTreeMap};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use aoc::{utils, solver::{Answer, Part, SolverError}};
+
+use crate::{registry, runner::{self, RunConfig}};
+
+/// Known-good answer for one part of a day on a given input
+///
+/// Stored in `data/NN/answers` as lines of `<input> <part> <answer>`, e.g. `example 1 17`.
+/// The answer is the rest of the line, line breaks of multi-line answers are written as `\n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub input: String,
+    pub part: Part,
+    pub answer: Answer,
+}
+
+#[derive(Error, Debug)]
+pub enum ParseExpectedError {
+    #[error("Expected '<input> <part> <answer>' but got: {0}")]
+    Malformed(String),
+    #[error(transparent)]
+    Part(#[from] SolverError),
+}
+
+impl FromStr for Expected {
+    type Err = ParseExpectedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || ParseExpectedError::Malformed(s.to_string());
+        let (input, rest) = s.trim().split_once(char::is_whitespace).ok_or_else(malformed)?;
+        let (part, answer) = rest.trim_start().split_once(char::is_whitespace).ok_or_else(malformed)?;
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Err(malformed());
+        }
+        Ok(Self {
+            input: input.to_string(),
+            part: part.parse()?,
+            answer: unescape(answer),
+        })
+    }
+}
+
+/// Answer with line breaks written as `\n`, as stored in the answers file
+fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(answer: &str) -> String {
+    let mut out = String::with_capacity(answer.len());
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            },
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            },
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn answers_path(day: usize) -> PathBuf {
+    runner::input_path(day, "answers")
+}
+
+/// Load expected answers of given day, `None` if there is no answers file
+pub fn load_answers(day: usize) -> Result<Option<Vec<Expected>>> {
+    let path = answers_path(day);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let answers = utils::parse_str(&fs::read_to_string(&path)?)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    Ok(Some(answers))
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub day: usize,
+    pub part: Part,
+    pub input: String,
+    pub expected: Answer,
+    pub actual: String,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {}, part {}, input {}: expected {}, got {}",
+            self.day, self.part, self.input, escape(&self.expected), escape(&self.actual))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub passed: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl CheckReport {
+    pub fn extend(&mut self, other: CheckReport) {
+        self.passed += other.passed;
+        self.mismatches.extend(other.mismatches);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CheckError {
+    #[error("{0} answers did not match")]
+    Failed(usize),
+    #[error("No answers to check")]
+    NoAnswers,
+    #[error("Answers can only be checked for inputs in the data directory, got {0}")]
+    NotInDataDir(String),
+}
+
+/// Name of the input to check, given as a name or a path within `data/NN` of all given days
+pub fn input_name(days: &[usize], input: &str) -> Result<String, CheckError> {
+    let path = Path::new(input);
+    match path.file_name().map(|name| name.to_string_lossy().to_string()) {
+        Some(name) if days.iter().all(|day| path == Path::new(&name) || path == runner::input_path(*day, &name)) => Ok(name),
+        _ => Err(CheckError::NotInDataDir(input.to_string())),
+    }
+}
+
+/// Compare answers of given day with the expected ones
+///
+/// If `only_input` is given then only answers for that input are checked.
+/// Only the parts selected in `config` are checked.
+pub fn check_day(day: usize, answers: &[Expected], only_input: Option<&str>, config: &RunConfig) -> Result<CheckReport> {
+    let solver = registry::get(day).ok_or(crate::DayError(day))?;
+
+    // group by input so that each input is solved only once
+    let mut by_input: BTreeMap<&str, Vec<&Expected>> = BTreeMap::new();
+    let selected = answers.iter()
+        .filter(|e| only_input.is_none_or(|i| i == e.input))
+        .filter(|e| config.parts.contains(&e.part));
+    for expected in selected {
+        by_input.entry(&expected.input).or_default().push(expected);
+    }
+
+    let mut report = CheckReport::default();
+    for (input, expected) in by_input {
+        let path = runner::input_path(day, input);
+        let run = fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+            .and_then(|content| runner::run(solver, &content, config));
+
+        for exp in expected {
+            let actual = match &run {
+                Ok(run) => match run.part(exp.part).map(|p| &p.answer) {
+                    Some(Ok(answer)) => answer.clone(),
+                    Some(Err(e)) => format!("error: {}", e),
+                    None => "nothing".to_string(),
+                },
+                Err(e) => format!("error: {}", e),
+            };
+            if actual == exp.answer {
+                report.passed += 1;
+            } else {
+                report.mismatches.push(Mismatch {
+                    day,
+                    part: exp.part,
+                    input: input.to_string(),
+                    expected: exp.answer.clone(),
+                    actual,
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::diag::Diag;
+
+    #[test]
+    fn expected() {
+        let e: Expected = "example 2 5".parse().unwrap();
+        assert_eq!(e, Expected { input: "example".to_string(), part: Part::Two, answer: "5".to_string() });
+        let e: Expected = "task 2 #.#\\n.#.".parse().unwrap();
+        assert_eq!(e.answer, "#.#\n.#.");
+        assert!(matches!("example 1".parse::<Expected>(), Err(ParseExpectedError::Malformed(_))));
+        assert!(matches!("example 3 5".parse::<Expected>(), Err(ParseExpectedError::Part(_))));
+    }
+
+    #[test]
+    fn mismatches() {
+        let answers = ["example 1 7", "example 2 6", "task 1 1692"].map(|line| line.parse().unwrap());
+        let config = RunConfig::new(None, Diag::default(), vec![]);
+        let report = check_day(1, &answers, Some("example"), &config).unwrap();
+        assert_eq!(report.passed, 1);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].to_string(), "Day 1, part 2, input example: expected 6, got 5");
+    }
+
+    #[test]
+    fn input_names() {
+        assert_eq!(input_name(&[1, 2], "example").unwrap(), "example");
+        assert_eq!(input_name(&[1], "data/01/example").unwrap(), "example");
+        assert!(input_name(&[1, 2], "data/01/example").is_err());
+        assert!(input_name(&[1], "/tmp/example").is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::Display;
 pub trait ColumnDisplay {
     type Item;
 
-    fn columns<'a>(&'a self) -> &'a [Self::Item];
+    fn columns(&self) -> &[Self::Item];
 
     fn column_display<'a>(&'a self, prefix: String, separator: String) -> Columns<'a, Self::Item> {
         Columns {
@@ -38,7 +38,7 @@ impl<'a, T> Display for Columns<'a, T>
                 break;
             }
             if i != 0 {
-                writeln!(f, "")?;
+                writeln!(f)?;
             }
             write!(f, "{}{}", self.prefix, lines.join(&self.separator))?;
         }
@@ -49,7 +49,7 @@ impl<'a, T> Display for Columns<'a, T>
 impl<T: Display> ColumnDisplay for &[T] {
     type Item = T;
 
-    fn columns<'a>(&'a self) -> &'a [Self::Item] {
+    fn columns(&self) -> &[Self::Item] {
         self
     }
 }
@@ -57,7 +57,7 @@ impl<T: Display> ColumnDisplay for &[T] {
 impl<T: Display> ColumnDisplay for &mut [T] {
     type Item = T;
 
-    fn columns<'a>(&'a self) -> &'a [Self::Item] {
+    fn columns(&self) -> &[Self::Item] {
         self
     }
 }
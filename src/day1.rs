@@ -1,3 +1,7 @@
+use anyhow::Result;
+
+use aoc::{utils, solver::{Solver, Description, Context, Answer}};
+
 // Part 1
 pub fn increasing_pairs_count(nums: &[isize]) -> usize {
     nums.windows(2)
@@ -12,3 +16,27 @@ pub fn windowed_increasing_count(nums: &[isize]) -> usize {
         .collect();
     increasing_pairs_count(&sums)
 }
+
+pub struct Day1;
+
+impl Solver for Day1 {
+    type Input = Vec<isize>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Sonar Sweep",
+        part_1: "Number of depth measurements larger than the previous one",
+        part_2: "Number of increases of sums of a three-measurement sliding window",
+    };
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok(utils::parse_str(input)?)
+    }
+
+    fn part_1(&self, nums: &Self::Input, _ctx: &Context) -> Result<Answer> {
+        Ok(increasing_pairs_count(nums).to_string())
+    }
+
+    fn part_2(&self, nums: &Self::Input, _ctx: &Context) -> Result<Answer> {
+        Ok(windowed_increasing_count(nums).to_string())
+    }
+}
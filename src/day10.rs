@@ -1,3 +1,7 @@
+use anyhow::Result;
+
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer}};
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Delimiter {
     Paren,
@@ -62,6 +66,18 @@ impl Bracket {
     }
 }
 
+/// Non-empty lines, which must consist of brackets only
+pub fn load_data<S: AsRef<str>>(lines: &[S]) -> ParseResult<Vec<String>> {
+    utils::numbered_lines(lines)
+        .map(|(number, line)| {
+            let chunks = line.trim();
+            match chunks.char_indices().find(|(_, c)| Bracket::from_char(*c).is_err()) {
+                Some((i, c)) => Err(ParseError::new(format!("Expected a bracket, got '{}'", c)).at(number, line, &chunks[i..])),
+                None => Ok(chunks.to_string()),
+            }
+        }).collect()
+}
+
 // Return:
 //   Ok: list of delimiters needed to complete a line (in order inner -> outer)
 //   Err: (column, Delimiter), when a wrong delimiter has been found
@@ -83,17 +99,15 @@ fn complete_line(line: &str) -> Result<Vec<Delimiter>, (usize, Delimiter)> {
     Ok(stack.iter().cloned().rev().collect())
 }
 
-pub fn part_1(lines: &[String], verbose: bool) -> usize {
+pub fn part_1(lines: &[String], diag: &Diag) -> usize {
     lines.iter()
-        .map(|line| complete_line(&line))
+        .map(|line| complete_line(line))
         .enumerate()
         .filter_map(|(line, result)| result.err()
                     .map(|(col, delim)| (line, col, delim)))
         .inspect(|(line, col, delim)| {
-            if verbose {
-                println!("Error on line {}, at column {}, delimiter = {}", 
-                     line, col, delim.chars().1);
-            }
+            info!(diag, "Error on line {}, at column {}, delimiter = {}",
+                line, col, delim.chars().1);
         })
         .map(|(_, _, delim)| delim.error_score())
         .sum()
@@ -104,9 +118,10 @@ fn completion_score(delims: &[Delimiter]) -> usize {
         .fold(0, |score, delim| score * 5 + delim.completion_score())
 }
 
-pub fn part_2(lines: &[String], verbose: bool) -> usize {
+/// Middle completion score, `None` unless there is an odd number of incomplete lines
+pub fn part_2(lines: &[String], diag: &Diag) -> Option<usize> {
     let mut scores: Vec<_> = lines.iter()
-        .map(|line| complete_line(&line))
+        .map(|line| complete_line(line))
         .enumerate()
         .filter_map(|(line, result)| result.ok()
                     .map(|delims| {
@@ -114,17 +129,40 @@ pub fn part_2(lines: &[String], verbose: bool) -> usize {
                         (line, delims, score)
                     }))
         .inspect(|(line, delims, score)| {
-            if verbose {
-                let delims: Vec<_> = delims.iter()
-                    .map(|d| d.chars().1.to_string())
-                    .collect();
-                println!("Completion for line {}: {} for {} points", 
-                     line, delims.join(""), score);
-            }
+            info!(diag, "Completion for line {}: {} for {} points", line,
+                delims.iter().map(|d| d.chars().1).collect::<String>(), score);
         })
         .map(|(_, _, score)| score)
         .collect();
     scores.sort_unstable();
-    assert!(scores.len() % 2 != 0, "Scores count is even");
-    scores[scores.len() / 2]
+    if scores.len() % 2 == 0 {
+        return None;
+    }
+    Some(scores[scores.len() / 2])
+}
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    type Input = Vec<String>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Syntax Scoring",
+        part_1: "Total syntax error score of corrupted lines",
+        part_2: "Middle completion score of incomplete lines",
+    };
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok(load_data(&input.lines().collect::<Vec<_>>())?)
+    }
+
+    fn part_1(&self, lines: &Self::Input, ctx: &Context) -> Result<Answer> {
+        Ok(part_1(lines, &ctx.diag).to_string())
+    }
+
+    fn part_2(&self, lines: &Self::Input, ctx: &Context) -> Result<Answer> {
+        part_2(lines, &ctx.diag)
+            .map(|score| score.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Expected an odd number of incomplete lines"))
+    }
 }
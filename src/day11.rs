@@ -1,7 +1,8 @@
 use std::{ops::{Deref, DerefMut}, collections::{HashSet, VecDeque}};
 
-use aoc::grid::{Grid, INVERSE};
+use aoc::{info, debug, diag::Diag, error::ParseResult, grid::{Grid, INVERSE}, utils, solver::{Solver, Description, Context, Answer, Param}};
 
+#[derive(Clone)]
 pub struct OctopusGrid(Grid<u8>);
 
 impl Deref for OctopusGrid {
@@ -19,16 +20,11 @@ impl DerefMut for OctopusGrid {
 }
 
 impl OctopusGrid {
-    pub fn new(lines: &[String]) -> Self {
-        let width = lines[0].len();
-        let nums = lines.iter()
-            .flat_map(|l| l.chars())
-            .map(|c| c.to_string().parse().expect("Failed to parse a number"));
-        let grid = Grid::from(nums, width).expect("Wrong number of cells");
-        Self(grid)
+    pub fn new(input: &str) -> ParseResult<Self> {
+        Ok(Self(utils::parse_digit_grid(input)?))
     }
 
-    pub fn step(&mut self, verbose: bool) -> HashSet<(usize, usize)> {
+    pub fn step(&mut self, diag: &Diag) -> HashSet<(usize, usize)> {
         let fmt = |val: &u8| if *val <= 9 {
             val.to_string()
         } else {
@@ -38,10 +34,7 @@ impl OctopusGrid {
         // increase all octopuses' energy levels by 1
         self.iter_mut()
             .for_each(|p| *p.value += 1);
-        if verbose {
-            let hl = |pos| self[pos] > 9;
-            println!("###\n{}", self.to_string(INVERSE, hl, fmt));
-        }
+        debug!(diag, "###\n{}", self.to_string(INVERSE, |pos| self[pos] > 9, fmt));
 
         // ones with level >9 flash: level of all neighbours += 1 (also diagonal)
         // each can flash at most once
@@ -77,29 +70,22 @@ impl OctopusGrid {
         flashed
     }
 
-    pub fn part_1(&mut self, steps: usize, verbose: bool) -> usize {
+    pub fn part_1(&mut self, steps: usize, diag: &Diag) -> usize {
         let mut flashes = 0;
-        if verbose {
-            let hl = |_| false;
-            let fmt = |val: &u8| val.to_string();
-            println!("Before:\n{}", self.to_string(INVERSE, hl, fmt));
-        }
+        let fmt = |val: &u8| val.to_string();
+        info!(diag, "Before:\n{}", self.to_string(INVERSE, |_| false, fmt));
         for step in 1..=steps {
-            let flashed = self.step(verbose);
+            let flashed = self.step(diag);
             flashes += flashed.len();
-            if verbose {
-                let hl = |pos| flashed.contains(&pos);
-                let fmt = |val: &u8| val.to_string();
-                println!("\nAfter step {}:\n{}", step, self.to_string(INVERSE, hl, fmt));
-            }
+            info!(diag, "\nAfter step {}:\n{}", step, self.to_string(INVERSE, |pos| flashed.contains(&pos), fmt));
         }
         flashes
     }
 
-    pub fn part_2(&mut self, verbose: bool) -> usize {
+    pub fn part_2(&mut self, diag: &Diag) -> usize {
         let mut step = 1;
         loop {
-            let flashes = self.step(verbose).len();
+            let flashes = self.step(diag).len();
             if flashes == self.width * self.height() {
                 return step;
             }
@@ -107,3 +93,31 @@ impl OctopusGrid {
         }
     }
 }
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = OctopusGrid;
+
+    const DESCRIPTION: Description = Description {
+        title: "Dumbo Octopus",
+        part_1: "Number of flashes after `steps` steps",
+        part_2: "First step during which all octopuses flash",
+    };
+
+    const PARAMS: &'static [Param] = &[
+        Param { name: "steps", default: "100", help: "Number of steps simulated in part 1" },
+    ];
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(OctopusGrid::new(input)?)
+    }
+
+    fn part_1(&self, grid: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(grid.clone().part_1(ctx.param("steps")?, &ctx.diag).to_string())
+    }
+
+    fn part_2(&self, grid: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(grid.clone().part_2(&ctx.diag).to_string())
+    }
+}
@@ -9,6 +9,8 @@ use std::{str::FromStr, collections::HashSet};
 
 use petgraph::graph::UnGraph;
 
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer}};
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 enum Cave {
     Start,
@@ -47,14 +49,12 @@ impl<I: Clone + Hash + Eq> SearchPath<I> {
 }
 
 impl Graph {
-    pub fn new(lines: &[String]) -> Self {
+    pub fn new<S: AsRef<str>>(lines: &[S]) -> ParseResult<Self> {
         let mut graph = UnGraph::new_undirected();
 
-        for line in lines {
-            let mut tokens = line.split("-");
-            let first: Cave = tokens.next().unwrap().parse().unwrap();
-            let second: Cave = tokens.next().unwrap().parse().unwrap();
-            assert!(tokens.next().is_none(), "{}", line);
+        for (number, line) in utils::numbered_lines(lines) {
+            let (first, second) = aoc::scan!(line, "{from}-{to}", from: Cave, to: Cave)
+                .map_err(|e| e.line(number, line))?;
 
             let mut get_or_add = |cave| {
                 graph.node_indices()
@@ -69,7 +69,12 @@ impl Graph {
             graph.add_edge(first, second, ());
         }
 
-        Self(graph)
+        for cave in [Cave::Start, Cave::End] {
+            if !graph.node_weights().any(|c| *c == cave) {
+                return Err(ParseError::new(format!("No '{}' cave", cave)));
+            }
+        }
+        Ok(Self(graph))
     }
 
     fn find_paths(&self, part_2: bool) -> Vec<Path> {
@@ -140,31 +145,24 @@ impl Graph {
         paths
     }
 
-    fn print_paths(&self, paths: &[Path]) {
+    fn print_paths(&self, paths: &[Path], diag: &Diag) {
         for path in paths {
-            for (i, cave) in path.caves.iter().enumerate() {
-                if i != 0 {
-                    print!(",");
-                }
-                print!("{}", cave);
-            }
-            println!();
+            info!(diag, "{}", path.caves.iter()
+                .map(|cave| cave.to_string())
+                .collect::<Vec<_>>()
+                .join(","));
         }
     }
 
-    pub fn part_1(&self, verbose: bool) -> usize {
+    pub fn part_1(&self, diag: &Diag) -> usize {
         let paths = self.find_paths(false);
-        if verbose {
-            self.print_paths(&paths);
-        }
+        self.print_paths(&paths, diag);
         paths.len()
     }
 
-    pub fn part_2(&self, verbose: bool) -> usize {
+    pub fn part_2(&self, diag: &Diag) -> usize {
         let paths = self.find_paths(true);
-        if verbose {
-            self.print_paths(&paths);
-        }
+        self.print_paths(&paths, diag);
         paths.len()
     }
 }
@@ -178,7 +176,7 @@ impl FromStr for Cave {
             "end" => Cave::End,
             name if name.chars().all(|c| c.is_ascii_uppercase()) => Cave::Big(name.into()),
             name if name.chars().all(|c| c.is_ascii_lowercase()) => Cave::Small(name.into()),
-            name => return Err(name.into()),
+            _ => return Err("cave names must be all lowercase or all uppercase".into()),
         };
         Ok(cave)
     }
@@ -195,6 +193,30 @@ impl Display for Cave {
     }
 }
 
+pub struct Day12;
+
+impl Solver for Day12 {
+    type Input = Graph;
+
+    const DESCRIPTION: Description = Description {
+        title: "Passage Pathing",
+        part_1: "Number of paths visiting small caves at most once",
+        part_2: "Number of paths when a single small cave can be visited twice",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(Graph::new(&input.lines().collect::<Vec<_>>())?)
+    }
+
+    fn part_1(&self, graph: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(graph.part_1(&ctx.diag).to_string())
+    }
+
+    fn part_2(&self, graph: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(graph.part_2(&ctx.diag).to_string())
+    }
+}
+
 // use typed_arena::Arena;
 //
 // pub struct Graph<'a> {
@@ -1,7 +1,9 @@
-use std::{io::{Read, BufReader, BufRead}, collections::HashSet};
+use std::{cmp::Ordering, collections::HashSet, fmt::Display};
+
+use aoc::{debug, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer}};
 
 #[derive(Debug, Clone)]
-struct Dots {
+pub struct Dots {
     dots: HashSet<(usize, usize)>,  // don't care for order, auto merge dots
     folds: Vec<Fold>,
 }
@@ -12,37 +14,43 @@ enum Fold {
     X(usize),
 }
 
+impl Fold {
+    /// Position of a dot after the fold, or why it cannot be folded
+    fn apply(self, (x, y): (usize, usize)) -> Result<(usize, usize), &'static str> {
+        let mirror = |c: usize, at: usize| match c.cmp(&at) {
+            Ordering::Less => Ok(c),
+            Ordering::Equal => Err("Fold line goes through a dot"),
+            Ordering::Greater => at.checked_sub(c - at).ok_or("Fold would move a dot past the edge"),
+        };
+        match self {
+            Fold::Y(y0) => Ok((x, mirror(y, y0)?)),
+            Fold::X(x0) => Ok((mirror(x, x0)?, y)),
+        }
+    }
+}
+
 impl Dots {
-    pub fn new(input: impl Read) -> Self {
-        let lines: Vec<_> = BufReader::new(input)
-            .lines()
-            .map(|l| l.unwrap())
-            .collect();
-        let dots: HashSet<_> = lines.iter()
-            .take_while(|l| !l.trim().is_empty())
-            .map(|l| {
-                let mut parts = l.trim().split(",");
-                let x = parts.next().unwrap().parse::<usize>().unwrap();
-                let y = parts.next().unwrap().parse::<usize>().unwrap();
-                (x, y)
-            }).collect();
-        let folds = lines.iter()
-            .rev()
-            .take_while(|l| !l.trim().is_empty())
-            .map(|l| {
-                assert!(l.contains("fold along "), "{}", l);
-                let mut parts = l.trim().split_whitespace()
-                    .nth(2)
-                    .unwrap()
-                    .split('=');
-                match parts.next().unwrap() {
-                    "x" => Fold::X(parts.next().unwrap().parse().unwrap()),
-                    "y" => Fold::Y(parts.next().unwrap().parse().unwrap()),
-                    s => panic!("Unexpected: {}", s),
-                }
-            }).collect();
-
-        Self { dots, folds }
+    pub fn new(input: &str) -> ParseResult<Self> {
+        let [dots, folds] = utils::split_sections(input)?;
+        let dots: HashSet<_> = dots.lines()
+            .map(|(number, line)| parse_dot(number, line))
+            .collect::<ParseResult<_>>()?;
+        // check that all folds can be done, so that folding never fails
+        let mut folded = dots.clone();
+        let mut folds = folds.lines()
+            .map(|(number, line)| {
+                let fold = parse_fold(number, line)?;
+                folded = folded.iter()
+                    .map(|dot| fold.apply(*dot))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| ParseError::new(e).line(number, line))?;
+                Ok(fold)
+            })
+            .collect::<ParseResult<Vec<_>>>()?;
+        // folds are applied from the end
+        folds.reverse();
+
+        Ok(Self { dots, folds })
     }
 
     pub fn folded(&self) -> Option<Self> {
@@ -50,28 +58,8 @@ impl Dots {
         let fold = new.folds.pop()?;
         // mirror all the dots
         new.dots = new.dots.iter()
-            .map(|(x, y)| {
-                match fold {
-                    Fold::Y(y0) => {
-                        if *y > y0 {
-                            (*x, y0 - (y - y0))
-                        } else if *y < y0 {
-                            (*x, *y)
-                        } else {
-                            panic!("Fold at dot: y = {}", y0);
-                        }
-                    },
-                    Fold::X(x0) => {
-                        if *x > x0 {
-                            (x0 - (x - x0), *y)
-                        } else if *x < x0 {
-                            (*x, *y)
-                        } else {
-                            panic!("Fold at dot: y = {}", x0);
-                        }
-                    },
-                }
-            }).collect();
+            .map(|dot| fold.apply(*dot).expect("Folds are checked when parsing"))
+            .collect();
         Some(new)
     }
 
@@ -79,43 +67,87 @@ impl Dots {
         self.dots.len()
     }
 
-    pub fn print(&self) {
-        let max_x = *self.dots.iter().map(|(x, _)| x).max().unwrap();
-        let max_y = *self.dots.iter().map(|(_, y)| y).max().unwrap();
+    pub fn print(&self, diag: &Diag) {
+        debug!(diag, "-----\n{}", self);
+    }
+}
+
+impl Display for Dots {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_x = *self.dots.iter().map(|(x, _)| x).max().ok_or(std::fmt::Error)?;
+        let max_y = *self.dots.iter().map(|(_, y)| y).max().ok_or(std::fmt::Error)?;
         for y in 0..=max_y {
+            if y != 0 {
+                writeln!(f)?;
+            }
             for x in 0..=max_x {
                 let s = if self.dots.contains(&(x, y)) {
                     "#"
                 } else {
                     "."
                 };
-                print!("{}", s);
+                write!(f, "{}", s)?;
             }
-            println!();
         }
+        Ok(())
     }
 }
 
-pub fn part_1(input: impl Read) -> usize {
-    let dots = Dots::new(input);
-    // println!("-----");
-    // dots.print();
+fn parse_dot(number: usize, line: &str) -> ParseResult<(usize, usize)> {
+    aoc::scan!(line, "{x},{y}", x: usize, y: usize)
+        .map_err(|e| e.line(number, line))
+}
 
+fn parse_fold(number: usize, line: &str) -> ParseResult<Fold> {
+    let (axis, position) = aoc::scan!(line, "fold along {axis}={position}", axis: char, position: usize)
+        .map_err(|e| e.line(number, line))?;
+    match axis {
+        'x' => Ok(Fold::X(position)),
+        'y' => Ok(Fold::Y(position)),
+        // the axis is right before '='
+        _ => Err(ParseError::new("Expected fold along x or y").line(number, line).column(line.find('=').unwrap_or(1))),
+    }
+}
+
+pub fn part_1(dots: &Dots, diag: &Diag) -> usize {
+    dots.print(diag);
     let dots = dots.folded().unwrap();
-    // println!("-----");
-    // dots.print();
+    dots.print(diag);
 
     dots.count()
 }
 
-pub fn part_2(input: impl Read) -> usize {
-    let mut dots = Dots::new(input);
+/// The answer is the code drawn by the dots
+pub fn part_2(dots: &Dots) -> String {
+    let mut dots = dots.clone();
 
     while let Some(new) = dots.folded() {
         dots = new;
     }
 
-    dots.print();
+    dots.to_string()
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    type Input = Dots;
+
+    const DESCRIPTION: Description = Description {
+        title: "Transparent Origami",
+        part_1: "Number of dots visible after the first fold",
+        part_2: "Code drawn by the dots after all folds",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(Dots::new(input)?)
+    }
+
+    fn part_1(&self, dots: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_1(dots, &ctx.diag).to_string())
+    }
 
-    0
+    fn part_2(&self, dots: &Self::Input, _ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_2(dots))
+    }
 }
@@ -1,36 +1,50 @@
-use std::{collections::HashMap, io::{BufReader, BufRead, Read}};
+use std::collections::HashMap;
 
-#[derive(Debug)]
-struct Polymerisator {
+use thiserror::Error;
+
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer, Param}};
+
+/// Longest polymer that is grown element by element, longer ones can only be counted
+pub const MAX_POLYMER_LEN: usize = 1 << 24;
+
+#[derive(Error, Debug)]
+#[error("Polymer of {0} elements could grow longer than {} elements", MAX_POLYMER_LEN)]
+pub struct PolymerTooLong(pub usize);
+
+#[derive(Debug, Clone)]
+pub struct Polymerisator {
     polymer: String,
     rules: HashMap<(char, char), String>,
 }
 
 impl Polymerisator {
-    pub fn new(input: impl Read) -> Self {
-        let reader = BufReader::new(input);
-        let mut lines = reader.lines().map(Result::unwrap);
-        let template = lines.next().unwrap().trim().to_string();
-        lines.next().unwrap();
-
-        let mut n = 0;
-        let rules: HashMap<_, _> = lines
-            .inspect(|_| n += 1)
-            .map(|line| {
-                let mut parts = line.split("->");
-                let from = parts.next().unwrap().trim();
-                let to = parts.next().unwrap().trim();
-                assert_eq!(from.len(), 2);
-                assert_eq!(to.len(), 1);
-                ((from.chars().nth(0).unwrap(), from.chars().nth(1).unwrap()), to.into())
-            }).collect();
-
-        assert_eq!(rules.len(), n);
-
-        Self { polymer: template, rules }
+    pub fn new(input: &str) -> ParseResult<Self> {
+        let [template, insertions] = utils::split_sections(input)?;
+        if let Some((number, line)) = template.lines().nth(1) {
+            return Err(ParseError::new("Expected a single line with the polymer template").line(number, line));
+        }
+        let template = template.text.trim().to_string();
+
+        let mut rules = HashMap::new();
+        for (number, line) in insertions.lines() {
+            let (pair, insertion) = aoc::scan!(line, "{pair} -> {insertion}", pair: String, insertion: char)
+                .map_err(|e| e.line(number, line))?;
+            let pair = match pair.chars().collect::<Vec<_>>()[..] {
+                [first, second] => (first, second),
+                _ => return Err(ParseError::new("Expected a pair of elements").at(number, line, &pair)),
+            };
+            if rules.insert(pair, insertion.to_string()).is_some() {
+                return Err(ParseError::new("Duplicate rule").line(number, line));
+            }
+        }
+
+        Ok(Self { polymer: template, rules })
     }
 
-    pub fn grow(&mut self) {
+    pub fn grow(&mut self) -> Result<(), PolymerTooLong> {
+        if self.polymer.len() * 2 > MAX_POLYMER_LEN + 1 {
+            return Err(PolymerTooLong(self.polymer.len()));
+        }
         // avoid some initial relocations, but don't try to be too smart with size guessing
         let mut new = String::with_capacity(self.polymer.len());
         // add the first one
@@ -45,6 +59,7 @@ impl Polymerisator {
             new.push(curr);
         }
         self.polymer = new;
+        Ok(())
     }
 
     pub fn polymer(&self) -> &str {
@@ -59,16 +74,19 @@ impl Polymerisator {
             })
     }
 
-    pub fn grow_and_get_counts(&self, steps: usize) -> HashMap<char, usize> {
+    /// Counts of elements after given steps, `None` if they do not fit in `u128`
+    pub fn grow_and_get_counts(&self, steps: usize) -> Option<HashMap<char, u128>> {
         // Store counts of polymer pairs, we do not need to know the order
-        let mut pair_counts: HashMap<(char, char), usize> = HashMap::new();
+        let mut pair_counts: HashMap<(char, char), u128> = HashMap::new();
         let pairs = self.polymer.chars().zip(self.polymer.chars().skip(1));
         for pair in pairs {
             *pair_counts.entry(pair).or_insert(0) += 1;
         }
 
         // Also update counts while growing
-        let mut counts = self.counts();
+        let mut counts: HashMap<char, u128> = self.counts().into_iter()
+            .map(|(c, n)| (c, n as u128))
+            .collect();
 
         // In each step iterate over exisiting paris, possibly grow and update counts
         let mut growth_step = |_i| {
@@ -91,17 +109,19 @@ impl Polymerisator {
                     *pair_counts.get_mut(&pair).unwrap() -= n;
                     // add the new ones
                     for new in new_pairs {
-                        *pair_counts.entry(new).or_insert(0) += n;
+                        let count = pair_counts.entry(new).or_insert(0);
+                        *count = count.checked_add(n)?;
                     }
                     // add the new char to counts
-                    *counts.entry(c).or_insert(0) += n;
+                    let count = counts.entry(c).or_insert(0);
+                    *count = count.checked_add(n)?;
                 }
             }
 
             // let counts_str = counts.iter()
             //     .filter(|(_, n)| **n > 0)
             //     .fold(String::new(), |s, (c, n)| format!("{} {}={}", s, c, n));
-            // let total_count: usize = counts.iter().map(|(_, n)| n).sum();
+            // let total_count: usize = counts.values().sum();
             // let pairs_str = pair_counts.iter()
             //     .filter(|(_, n)| **n > 0)
             //     .fold(String::new(), |s, (c, n)| format!("{} {}{}={}", s, c.0, c.1, n));
@@ -109,50 +129,101 @@ impl Polymerisator {
             // assert_eq!(total_pairs, total_count - 1);
             // println!("After  {}:\n  counts:{} ({})\n  pairs: {}", i, counts_str, total_count, pairs_str);
 
+            Some(())
         };
 
         for step in 1 ..= steps {
-            growth_step(step);
+            growth_step(step)?;
         }
 
-        counts
+        Some(counts)
     }
 }
 
-pub fn part_1(input: impl Read, verbose: bool) -> usize {
-    let mut p = Polymerisator::new(input);
+pub fn part_1(p: &Polymerisator, steps: usize, diag: &Diag) -> Result<usize, PolymerTooLong> {
+    let mut p = p.clone();
 
-    if verbose {
-        println!("Template: {}", p.polymer());
-    }
-    for step in 1..=10 {
-        p.grow();
-        if verbose {
-            if p.polymer.len() > 100 {
-                println!("After {:2}: {}... ({})", step, &p.polymer()[..100], p.polymer().len());
-            } else {
-                println!("After {:2}: {} ({})", step, p.polymer(), p.polymer().len());
-            }
+    info!(diag, "Template: {}", p.polymer());
+    for step in 1..=steps {
+        p.grow()?;
+        if p.polymer.len() > 100 {
+            info!(diag, "After {:2}: {}... ({})", step, &p.polymer()[..100], p.polymer().len());
+        } else {
+            info!(diag, "After {:2}: {} ({})", step, p.polymer(), p.polymer().len());
         }
     }
 
     let counts = p.counts();
-    let most_common = counts.iter().map(|(_, n)| n).max().unwrap();
-    let least_common = counts.iter().map(|(_, n)| n).min().unwrap();
+    let most_common = counts.values().max().unwrap();
+    let least_common = counts.values().min().unwrap();
 
-    most_common - least_common
+    Ok(most_common - least_common)
 }
 
 
 // Gotta be smarter than brute force now...
 // find stable cycles?
-pub fn part_2(input: impl Read, _verbose: bool) -> usize {
-    let p = Polymerisator::new(input);
+pub fn part_2(p: &Polymerisator, steps: usize) -> Option<u128> {
+    let counts = p.grow_and_get_counts(steps)?;
+
+    let most_common = counts.values().max().unwrap();
+    let least_common = counts.values().min().unwrap();
+
+    Some(most_common - least_common)
+}
 
-    let counts = p.grow_and_get_counts(40);
+pub struct Day14;
 
-    let most_common = counts.iter().map(|(_, n)| n).max().unwrap();
-    let least_common = counts.iter().map(|(_, n)| n).min().unwrap();
+impl Solver for Day14 {
+    type Input = Polymerisator;
 
-    most_common - least_common
+    const DESCRIPTION: Description = Description {
+        title: "Extended Polymerization",
+        part_1: "Most common minus least common element after `steps_1` steps",
+        part_2: "Most common minus least common element after `steps_2` steps",
+    };
+
+    const PARAMS: &'static [Param] = &[
+        Param { name: "steps_1", default: "10", help: "Number of insertion steps in part 1" },
+        Param { name: "steps_2", default: "40", help: "Number of insertion steps in part 2" },
+    ];
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(Polymerisator::new(input)?)
+    }
+
+    fn part_1(&self, p: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_1(p, ctx.param("steps_1")?, &ctx.diag)?.to_string())
+    }
+
+    fn part_2(&self, p: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let steps = ctx.param("steps_2")?;
+        part_2(p, steps)
+            .map(|answer| answer.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Element counts overflow after {} steps", steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow_limit() {
+        let mut p = Polymerisator::new("NN\n\nNN -> C\n").unwrap();
+        p.grow().unwrap();
+        assert_eq!(p.polymer(), "NCN");
+
+        p.polymer = "N".repeat(MAX_POLYMER_LEN / 2 + 1);
+        assert!(p.grow().is_err());
+        assert_eq!(p.polymer().len(), MAX_POLYMER_LEN / 2 + 1);
+    }
+
+    #[test]
+    fn count_overflow() {
+        let p = Polymerisator::new("NNC\n\nNN -> N\n").unwrap();
+        assert_eq!(part_2(&p, 10), Some(1 << 10));
+        assert_eq!(part_2(&p, 127), Some(1 << 127));
+        assert_eq!(part_2(&p, 128), None);
+    }
 }
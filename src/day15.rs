@@ -1,18 +1,9 @@
-use std::{io::{Read, BufReader, BufRead}, collections::{BinaryHeap, HashMap, HashSet}};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use aoc::grid::{Grid, BOLD, Position};
+use aoc::{info, diag::Diag, utils, grid::{Grid, BOLD, Position}, solver::{Solver, Description, Context, Answer, Param}};
 
-fn load_grid(input: impl Read) -> Grid<u32> {
-    let vals: Vec<_> = BufReader::new(input)
-        .lines()
-        .map(Result::unwrap)
-        .map(|l| l.chars()
-             .map(|c| c.to_digit(10).unwrap())
-             .collect::<Vec<_>>())
-        .collect();
-    let width = vals[0].len();
-    Grid::from(vals.into_iter().flatten(), width).unwrap()
-}
+/// Largest map that is searched for a path, repeating the map must not exceed it
+pub const MAX_CELLS: usize = 1 << 24;
 
 struct PathNode {
     pos: Position,
@@ -30,9 +21,8 @@ impl PathNode {
     // }
 
     fn distance(&self) -> usize {
-        let abs_diff = |a, b| if a > b { a - b } else { b - a };
-        let dx = abs_diff(self.pos.0, self.goal.0);
-        let dy = abs_diff(self.pos.1, self.goal.1);
+        let dx = self.pos.0.abs_diff(self.goal.0);
+        let dy = self.pos.1.abs_diff(self.goal.1);
         ((dx as f32).powi(2) + (dy as f32).powi(2)).sqrt().floor() as usize
     }
 }
@@ -47,7 +37,7 @@ impl Eq for PathNode {}
 
 impl PartialOrd for PathNode {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.distance().cmp(&other.distance()))
+        Some(self.cmp(other))
     }
 }
 
@@ -106,15 +96,13 @@ fn a_star(grid: &Grid<u32>, start: Position, goal: Position) -> Option<Vec<Posit
     None
 }
 
-fn solve(grid: Grid<u32>, verbose: bool) -> usize {
-    let (start, goal) =  ((0, 0), (grid.width - 1, grid.width - 1));
-    let path = a_star(&grid, start, goal).unwrap();
+fn solve(grid: &Grid<u32>, diag: &Diag) -> Option<usize> {
+    let (start, goal) =  ((0, 0), (grid.width - 1, grid.height() - 1));
+    let path = a_star(grid, start, goal)?;
     let mut path_nodes = HashSet::new();
     path.iter().for_each(|pos| { path_nodes.insert(pos); });
 
-    if verbose {
-        println!("{}", grid.to_string(BOLD, |pos| path_nodes.contains(&pos), |val| val.to_string()));
-    }
+    info!(diag, "{}", grid.to_string(BOLD, |pos| path_nodes.contains(&pos), |val| val.to_string()));
 
     // println!("Path: {:?}", path);
     let total_risk = path.iter()
@@ -122,23 +110,24 @@ fn solve(grid: Grid<u32>, verbose: bool) -> usize {
         .map(|pos| grid[*pos] as usize)
         .sum();
 
-    total_risk
+    Some(total_risk)
 }
 
-pub fn part_1(input: impl Read, verbose: bool) -> usize {
-    let grid = load_grid(input);
+pub fn part_1(grid: &Grid<u32>, diag: &Diag) -> Option<usize> {
     // println!("{}", grid.to_string(BOLD, |_| false, |val| val.to_string()));
 
-    solve(grid, verbose)
+    solve(grid, diag)
 }
 
 
-pub fn part_2(input: impl Read, verbose: bool) -> usize {
-    let grid = load_grid(input);
-
+pub fn part_2(grid: &Grid<u32>, tiles: usize, diag: &Diag) -> anyhow::Result<usize> {
+    let cells = tiles.checked_mul(tiles).and_then(|n| n.checked_mul(grid.width * grid.height()));
+    if cells.is_none_or(|cells| cells > MAX_CELLS) {
+        anyhow::bail!("Map repeated {} times would have more than {} cells", tiles, MAX_CELLS);
+    }
     // expand grid
-    let new_width = 5 * grid.width;
-    let n = new_width * grid.height() * 5;
+    let new_width = tiles * grid.width;
+    let n = new_width * grid.height() * tiles;
     let mut new = Grid::from([0].iter().cycle().take(n).copied(), new_width).unwrap();
 
     let points: Vec<_> = grid.iter()
@@ -147,14 +136,60 @@ pub fn part_2(input: impl Read, verbose: bool) -> usize {
 
     for p in &points {
         // replicate the point module 10
-        for i in 0..5 {
-            for j in 0..5 {
-                let pos = (p.0 + i * grid.width, p.1 + j * grid.width);
+        for i in 0..tiles {
+            for j in 0..tiles {
+                let pos = (p.0 + i * grid.width, p.1 + j * grid.height());
                 let val = p.2 + (i + j) as u32;
                 new[pos] = if val > 9 { val % 10 + 1 } else { val };
             }
         }
     }
 
-    solve(new, verbose)
+    solve(&new, diag).ok_or_else(|| anyhow::anyhow!(NO_PATH))
+}
+
+const NO_PATH: &str = "No path to the bottom right corner";
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    type Input = Grid<u32>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Chiton",
+        part_1: "Lowest total risk of a path through the map",
+        part_2: "Lowest total risk of a path through the map repeated `tiles` times in each direction",
+    };
+
+    const PARAMS: &'static [Param] = &[
+        Param { name: "tiles", default: "5", help: "How many times the map is repeated in each direction in part 2" },
+    ];
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(utils::parse_digit_grid(input)?)
+    }
+
+    fn part_1(&self, grid: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_1(grid, &ctx.diag).ok_or_else(|| anyhow::anyhow!(NO_PATH))?.to_string())
+    }
+
+    fn part_2(&self, grid: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_2(grid, ctx.param_in("tiles", 1..)?, &ctx.diag)?.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangular() {
+        let diag = Diag::default();
+        let grid = utils::parse_digit_grid::<u32>("12\n34\n56").unwrap();
+        assert_eq!(part_1(&grid, &diag), Some(12));
+        let grid = utils::parse_digit_grid::<u32>("123\n456").unwrap();
+        assert_eq!(part_1(&grid, &diag), Some(11));
+        assert_eq!(part_2(&grid, 2, &diag).unwrap(), 32);
+        assert!(part_2(&grid, 1 << 12, &diag).is_err());
+    }
 }
@@ -1,7 +1,9 @@
 use std::io::Read;
 
+use aoc::{debug, diag::Diag, error::{ParseError, ParseResult}, solver::{Solver, Description, Context, Answer}};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Packet {
+pub enum Packet {
     LiteralValue {
         version: u8,
         // type_id: u8,  always 4
@@ -16,7 +18,7 @@ enum Packet {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Operation {
+pub enum Operation {
     Sum,
     Product,
     Minimum,
@@ -74,56 +76,67 @@ impl Operation {
 }
 
 impl Packet {
-    fn from_bits(bits: &mut Bits) -> Self {
-        let version = bits.next(3) as u8;
-        let type_id = bits.next(3) as u8;
+    fn from_bits(bits: &mut Bits) -> ParseResult<Self> {
+        let start = bits.current();
+        let version = bits.next(3)? as u8;
+        let type_id = bits.next(3)? as u8;
 
         // println!("version =  {0} = {0:08b}", version);
         // println!("type_id =  {0} = {0:08b}", type_id);
 
         if type_id == 4 {
-            return Self::LiteralValue {
+            return Ok(Self::LiteralValue {
                 version,
-                value: Self::literal_value(bits),
-            }
+                value: Self::literal_value(bits)?,
+            })
         }
 
         // if not then we are an operator packet
         let mut subpackets = Vec::new();
 
-        let length_id = bits.next(1);
+        let length_id = bits.next(1)?;
         match length_id {
             0 => {  // total length in bits of subpackets
-                let mut n_bits = bits.next(15);
+                let n_bits = bits.next(15)?;
                 let start = bits.current();
                 while bits.current() - start < n_bits {
-                    let packet = Packet::from_bits(bits);
+                    let packet = Packet::from_bits(bits)?;
                     subpackets.push(packet);
                 }
             },
-            1 => {  // number of subpackets
-                let n_packets = bits.next(11);
+            _ => {  // number of subpackets
+                let n_packets = bits.next(11)?;
                 for _ in 0..n_packets {
-                    let packet = Packet::from_bits(bits);
+                    let packet = Packet::from_bits(bits)?;
                     subpackets.push(packet);
                 }
             },
-            _ => panic!(),
         }
 
-        let operation = Operation::try_from(type_id).unwrap();
+        let operation = Operation::try_from(type_id)
+            .map_err(|id| bits.error(start, format!("Unknown packet type: {}", id)))?;
+        let expected = match operation {
+            Operation::Sum | Operation::Product => None,
+            Operation::Minimum | Operation::Maximum if !subpackets.is_empty() => None,
+            Operation::Minimum | Operation::Maximum => Some("at least 1"),
+            _ if subpackets.len() == 2 => None,
+            _ => Some("2"),
+        };
+        if let Some(expected) = expected {
+            return Err(bits.error(start, format!("{:?} packet needs {} subpackets, got {}", operation, expected, subpackets.len())));
+        }
 
-        Self::Operator {
+        Ok(Self::Operator {
             version,
             operation,
             subpackets
-        }
+        })
     }
 
-    fn literal_value(bits: &mut Bits) -> usize {
+    fn literal_value(bits: &mut Bits) -> ParseResult<usize> {
         let mut value = 0;
         loop {
-            let five = bits.next(5);
+            let five = bits.next(5)?;
             value |= five & 0b1111;
             let more = (five & (1 << 4)) != 0;
             if more {
@@ -132,7 +145,7 @@ impl Packet {
                 break;
             }
         }
-        value
+        Ok(value)
     }
 
     fn sum_versions(&self) -> usize {
@@ -156,35 +169,37 @@ impl Packet {
     }
 }
 
-pub fn part_1(input: impl Read, verbose: bool) -> usize {
-    let bytes = load_data(input);
-    let packet = Packet::from_bits(&mut bytes.as_slice().into());
-    if verbose {
-        println!("{:#?}", packet);
-    }
+fn load_packet(input: impl Read) -> ParseResult<Packet> {
+    let bytes = load_data(input)?;
+    Packet::from_bits(&mut bytes.as_slice().into())
+}
+
+pub fn part_1(packet: &Packet, diag: &Diag) -> usize {
+    debug!(diag, "{:#?}", packet);
     packet.sum_versions()
 }
 
-pub fn part_2(input: impl Read, verbose: bool) -> usize {
-    let bytes = load_data(input);
-    let packet = Packet::from_bits(&mut bytes.as_slice().into());
+pub fn part_2(packet: &Packet) -> usize {
     packet.eval()
 }
 
-fn load_data(mut input: impl Read) -> Vec<u8> {
+fn load_data(mut input: impl Read) -> ParseResult<Vec<u8>> {
     let mut s = String::new();
-    input.read_to_string(&mut s).unwrap();
-
-    let digits: Vec<_> = s.chars()
-        .filter(|c| !c.is_whitespace())
-        .map(|c| c.to_digit(16))
-        .map(Option::unwrap)
-        .collect();
+    input.read_to_string(&mut s).map_err(ParseError::new)?;
+
+    let mut digits = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        for (j, c) in line.char_indices().filter(|(_, c)| !c.is_whitespace()) {
+            let digit = c.to_digit(16)
+                .ok_or_else(|| ParseError::new(format!("Expected a hexadecimal digit, got '{}'", c)).at(i + 1, line, &line[j..]))?;
+            digits.push(digit);
+        }
+    }
 
-    digits.chunks(2)
+    Ok(digits.chunks(2)
         .map(|nibbles| (nibbles[0] << 4) | *nibbles.get(1).unwrap_or(&0))
         .map(|val| val as u8)
-        .collect()
+        .collect())
 }
 
 struct Bits<'a> {
@@ -197,10 +212,18 @@ impl<'a> Bits<'a> {
         Self { bytes, current: 0 }
     }
 
-    pub fn next(&mut self, count: usize) -> usize {
+    pub fn next(&mut self, count: usize) -> ParseResult<usize> {
+        if self.current + count > self.bytes.len() * 8 {
+            return Err(self.error(self.current, "Packet is cut short"));
+        }
         let value = get_bits(self.bytes, self.current, count);
         self.current += count;
-        value
+        Ok(value)
+    }
+
+    /// Error in packet starting at given bit, the column is the hexadecimal digit
+    fn error(&self, bit: usize, reason: impl std::fmt::Display) -> ParseError {
+        ParseError::new(reason).column(bit / 4 + 1)
     }
 
     pub fn current(&self) -> usize {
@@ -232,6 +255,30 @@ fn get_bits(data: &[u8], offset: usize, count: usize) -> usize {
     value
 }
 
+pub struct Day16;
+
+impl Solver for Day16 {
+    type Input = Packet;
+
+    const DESCRIPTION: Description = Description {
+        title: "Packet Decoder",
+        part_1: "Sum of version numbers of all packets",
+        part_2: "Value of the expression encoded by the outermost packet",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(load_packet(input.as_bytes())?)
+    }
+
+    fn part_1(&self, packet: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_1(packet, &ctx.diag).to_string())
+    }
+
+    fn part_2(&self, packet: &Self::Input, _ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_2(packet).to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,16 +297,16 @@ mod tests {
 
     #[test]
     fn data_loading() {
-        let bytes = load_data("D2FE28".as_bytes());
+        let bytes = load_data("D2FE28".as_bytes()).unwrap();
         assert_eq!(bytes, vec![0xd2, 0xfe, 0x28]);
-        let bytes = load_data("D2FE28a".as_bytes());
+        let bytes = load_data("D2FE28a".as_bytes()).unwrap();
         assert_eq!(bytes, vec![0xd2, 0xfe, 0x28, 0xa0]);
     }
 
     #[test]
     fn literal_value() {
-        let bytes = load_data("D2FE28".as_bytes());
-        let packet = Packet::from_bits(&mut bytes.as_slice().into());
+        let bytes = load_data("D2FE28".as_bytes()).unwrap();
+        let packet = Packet::from_bits(&mut bytes.as_slice().into()).unwrap();
         assert_eq!(packet, Packet::LiteralValue {
             version: 0b110,
             value: 2021,
@@ -268,8 +315,8 @@ mod tests {
 
     #[test]
     fn operator_total_len() {
-        let bytes = load_data("38006F45291200".as_bytes());
-        let packet = Packet::from_bits(&mut bytes.as_slice().into());
+        let bytes = load_data("38006F45291200".as_bytes()).unwrap();
+        let packet = Packet::from_bits(&mut bytes.as_slice().into()).unwrap();
         assert_eq!(packet, Packet::Operator {
             version: 0b001,
             operation: Operation::LessThan,
@@ -288,8 +335,8 @@ mod tests {
 
     #[test]
     fn operator_n_subpackets() {
-        let bytes = load_data("EE00D40C823060".as_bytes());
-        let packet = Packet::from_bits(&mut bytes.as_slice().into());
+        let bytes = load_data("EE00D40C823060".as_bytes()).unwrap();
+        let packet = Packet::from_bits(&mut bytes.as_slice().into()).unwrap();
         assert_eq!(packet, Packet::Operator {
             version: 0b111,
             operation: Operation::Maximum,
@@ -311,8 +358,8 @@ mod tests {
     }
 
     fn sum_versions(s: &str) -> usize {
-        let bytes = load_data(s.as_bytes());
-        let packet = Packet::from_bits(&mut bytes.as_slice().into());
+        let bytes = load_data(s.as_bytes()).unwrap();
+        let packet = Packet::from_bits(&mut bytes.as_slice().into()).unwrap();
         packet.sum_versions()
     }
 
@@ -329,8 +376,8 @@ mod tests {
     fn sum_versions_4() { assert_eq!(sum_versions("A0016C880162017C3686B18A3D4780"), 31); }
 
     fn eval(s: &str) -> usize {
-        let bytes = load_data(s.as_bytes());
-        let packet = Packet::from_bits(&mut bytes.as_slice().into());
+        let bytes = load_data(s.as_bytes()).unwrap();
+        let packet = Packet::from_bits(&mut bytes.as_slice().into()).unwrap();
         packet.eval()
     }
 
@@ -1,9 +1,9 @@
 use std::io::Read;
 
-use regex::Regex;
+use aoc::{info, debug, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer, Param}};
 
 #[derive(Debug)]
-struct Target {
+pub struct Target {
     x_start: i32,
     x_end: i32,
     y_start: i32,
@@ -55,30 +55,31 @@ impl Target {
     }
 }
 
-fn load_data(mut input: impl Read) -> Target {
+fn load_data(mut input: impl Read) -> ParseResult<Target> {
     let mut s = String::new();
-    input.read_to_string(&mut s).unwrap();
-    let re = Regex::new(r"target area: x=(-?\d+)..(-?\d+), y=(-?\d+)..(-?\d+)").unwrap();
-    let captures = re.captures(&s).unwrap();
-    let x1: i32 = captures[1].parse().unwrap();
-    let x2: i32 = captures[2].parse().unwrap();
-    let y1: i32 = captures[3].parse().unwrap();
-    let y2: i32 = captures[4].parse().unwrap();
-    Target {
+    input.read_to_string(&mut s).map_err(ParseError::new)?;
+    let lines: Vec<_> = s.lines().collect();
+    let (number, line) = utils::numbered_lines(&lines).next()
+        .ok_or_else(|| ParseError::new("No target area"))?;
+    let (x1, x2, y1, y2) = aoc::scan!(line, "target area: x={x1}..{x2}, y={y1}..{y2}", x1: i32, x2: i32, y1: i32, y2: i32)
+        .map_err(|e| e.line(number, line))?;
+    Ok(Target {
         x_start: x1.min(x2),
         x_end: x1.max(x2),
         y_start: y1.min(y2),
         y_end: y1.max(y2),
-    }
+    })
 }
 
-fn display(target: &Target, path: &[(i32, i32)]) {
+#[allow(dead_code)]
+fn display(target: &Target, path: &[(i32, i32)], diag: &Diag) {
     let min_x = target.x_start.min(*path.iter().map(|(x, _)| x).min().unwrap_or(&target.x_start));
     let max_x = target.x_end.max(*path.iter().map(|(x, _)| x).max().unwrap_or(&target.x_end));
     let min_y = target.y_start.min(*path.iter().map(|(_, y)| y).min().unwrap_or(&target.y_start));
     let max_y = target.y_end.max(*path.iter().map(|(_, y)| y).max().unwrap_or(&target.y_end));
 
     for y in (min_y ..= max_y).rev() {
+        let mut line = String::new();
         for x in min_x ..= max_x {
             let pos = (x, y);
             let c = if pos == (0, 0) {
@@ -90,9 +91,9 @@ fn display(target: &Target, path: &[(i32, i32)]) {
             } else {
                 "."
             };
-            print!("{}", c);
+            line += c;
         }
-        println!();
+        info!(diag, "{}", line);
     }
 }
 
@@ -103,8 +104,8 @@ fn highest_point(path: &[(i32, i32)]) -> i32 {
         .unwrap()
 }
 
-pub fn part_1(input: impl Read, verbose: bool) -> usize {
-    let target = load_data(input);
+/// Highest position reached by a probe hitting the target, `None` if no probe hits it
+pub fn part_1(target: &Target, max_y: i32) -> Option<usize> {
 
     // Examples
     // for vel in [(7, 2), (6, 3), (9, 0), (17, -4)] {
@@ -115,69 +116,87 @@ pub fn part_1(input: impl Read, verbose: bool) -> usize {
 
     // X "range" is a sum of an arithmetic series (x,x-1,x-2,...) with (x+1) elements
     // xr = N * (x + 0) / 2
-    let x_range = |x: i32| (x + 1) * (x + 0) / 2;
+    let x_range = |x: i32| (x + 1) * x / 2;
     let xs = (0..)
         .skip_while(|x| x_range(*x) < target.x_start)
         .take_while(|x| x_range(*x) <= target.x_end);
 
     // Test all Ys starting from the one that would reach bottom of target in 1 iteration,
     // and doing this <how long?>
-    let mut highest = i32::MIN;
-    let mut best_path = vec![];
+    let mut highest = None;
     for y in target.y_start.. {
         for x in xs.clone() {
             let (ok, path) = target.try_shoot((x, y));
             if ok {
-                highest = highest.max(highest_point(&path));
-                best_path = path;
+                highest = highest.max(Some(highest_point(&path)));
             }
         }
 
         // brute force!
-        if y > 1000 {
+        if y > max_y {
             break;
         }
     }
 
-    // if verbose {
-    //     display(&target, &best_path);
-    // }
-
-    highest as usize
+    // paths start at y = 0, so the highest point is never negative
+    highest.map(|y| y as usize)
 }
 
-pub fn part_2(input: impl Read, verbose: bool) -> usize {
-    let target = load_data(input);
-
-    // and built in --release mode!
-    const BRUTE_FORCE: i32 = 5000;
+// and built in --release mode!
+pub fn part_2(target: &Target, brute_force: i32, diag: &Diag) -> usize {
 
     // X "range" is a sum of an arithmetic series (x,x-1,x-2,...) with (x+1) elements
     // xr = N * (x + 0) / 2
-    let x_range = |x: i32| (x + 1) * (x + 0) / 2;
+    let x_range = |x: i32| (x + 1) * x / 2;
     let xs = (0..)
         .skip_while(|x| x_range(*x) < target.x_start)
-        .take(BRUTE_FORCE as usize);
+        .take(brute_force as usize);
 
     // Test all Ys starting from the one that would reach bottom of target in 1 iteration,
     // and doing this <how long?>
-    let mut highest = i32::MIN;
-    let mut best_path = vec![];
     let mut vels = vec![];
-    for y in target.y_start..BRUTE_FORCE {
+    for y in target.y_start..brute_force {
         for x in xs.clone() {
-            let (ok, path) = target.try_shoot((x, y));
+            let (ok, _path) = target.try_shoot((x, y));
             if ok {
-                highest = highest.max(highest_point(&path));
-                best_path = path;
                 vels.push((x, y));
             }
         }
     }
 
-    if verbose {
-        println!("Velocites:\n{:?}", vels);
-    }
+    debug!(diag, "Velocites:\n{:?}", vels);
 
     vels.len()
 }
+
+pub struct Day17;
+
+impl Solver for Day17 {
+    type Input = Target;
+
+    const DESCRIPTION: Description = Description {
+        title: "Trick Shot",
+        part_1: "Highest y position reached by a probe that hits the target",
+        part_2: "Number of initial velocities of probes that hit the target",
+    };
+
+    const PARAMS: &'static [Param] = &[
+        Param { name: "max_y", default: "1000", help: "Highest initial y velocity tried in part 1" },
+        Param { name: "brute_force", default: "5000", help: "Number of x and highest y velocity tried in part 2" },
+    ];
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(load_data(input.as_bytes())?)
+    }
+
+    fn part_1(&self, target: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let max_y = ctx.param("max_y")?;
+        part_1(target, max_y)
+            .map(|y| y.to_string())
+            .ok_or_else(|| anyhow::anyhow!("No probe with y velocity up to {} hits the target", max_y))
+    }
+
+    fn part_2(&self, target: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_2(target, ctx.param_in("brute_force", 1..)?, &ctx.diag).to_string())
+    }
+}
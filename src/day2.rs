@@ -1,5 +1,7 @@
 use std::{error::Error, fmt::Display, num::ParseIntError, str::FromStr};
 
+use aoc::{info, utils, solver::{Solver, Description, Context, Answer}};
+
 #[derive(Debug, Clone)]
 pub enum Command {
     Forward(usize),
@@ -31,7 +33,7 @@ impl FromStr for Command {
 
         // we can parse number here as all commands use a single numeric arg
         let arg: usize = arg.parse()
-            .map_err(|e| ParseCommandError::WrongArgument(e))?;
+            .map_err(ParseCommandError::WrongArgument)?;
 
         // dispatch command type
         match cmd {
@@ -45,7 +47,14 @@ impl FromStr for Command {
 
 impl Display for ParseCommandError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Failed to parse command: {:?}", self)
+        write!(f, "Failed to parse command: ")?;
+        match self {
+            ParseCommandError::MissingName => write!(f, "missing name"),
+            ParseCommandError::MissingArgument => write!(f, "missing argument"),
+            ParseCommandError::ExtraTokens(n) => write!(f, "{} extra tokens", n),
+            ParseCommandError::WrongArgument(e) => write!(f, "wrong argument: {}", e),
+            ParseCommandError::UnknownCommand(cmd) => write!(f, "unknown command: {}", cmd),
+        }
     }
 }
 
@@ -73,15 +82,16 @@ impl Position {
     }
 
     // Part 1
-    pub fn update1(&mut self, cmd: Command) {
+    pub fn update1(&mut self, cmd: Command) -> anyhow::Result<()> {
         match cmd {
             Command::Forward(n) => self.horizontal += n as isize,
             Command::Down(n) => self.depth += n as isize,
             Command::Up(n) => {
                 self.depth -= n as isize;
-                assert!(self.depth >= 0, "Negative depth: {}", self.depth);
+                anyhow::ensure!(self.depth >= 0, "Negative depth: {}", self.depth);
             },
         }
+        Ok(())
     }
 
     // Part 2
@@ -97,12 +107,56 @@ impl Position {
     }
 }
 
-pub fn move_by<F>(commands: &[Command], mut update: F) -> Position
-    where F: FnMut(&mut Position, Command)
+pub fn move_by<F>(commands: &[Command], mut update: F) -> anyhow::Result<Position>
+    where F: FnMut(&mut Position, Command) -> anyhow::Result<()>
 {
     let mut pos = Position::new();
-    for cmd in commands.iter() {
+    for (i, cmd) in commands.iter().enumerate() {
         update(&mut pos, cmd.clone())
+            .map_err(|e| anyhow::anyhow!("{} after command {}", e, i + 1))?;
+    }
+    Ok(pos)
+}
+
+pub struct Day2;
+
+impl Solver for Day2 {
+    type Input = Vec<Command>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Dive!",
+        part_1: "Product of the final horizontal position and depth",
+        part_2: "Product of the final horizontal position and depth when the commands change aim",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(utils::parse_str_all(input)?)
+    }
+
+    fn part_1(&self, cmds: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let pos = move_by(cmds, |pos, cmd| pos.update1(cmd))?;
+        info!(ctx.diag, "position: {:?}", pos);
+        Ok(pos.result().to_string())
+    }
+
+    fn part_2(&self, cmds: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let pos = move_by(cmds, |pos, cmd| {
+            pos.update2(cmd);
+            Ok(())
+        })?;
+        info!(ctx.diag, "position: {:?}", pos);
+        Ok(pos.result().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_depth() {
+        let cmds: Vec<Command> = utils::parse_str_all("forward 5\nup 3\n").unwrap();
+        let e = move_by(&cmds, |pos, cmd| pos.update1(cmd)).unwrap_err();
+        assert_eq!(e.to_string(), "Negative depth: -3 after command 2");
     }
-    pos
 }
@@ -1,5 +1,7 @@
 use std::{fmt::Display, collections::HashMap, str::FromStr};
 
+use aoc::{info, debug, diag::Diag, error::ParseError, utils, solver::{Solver, Description, Context, Answer}};
+
 #[derive(Debug)]
 pub struct UnexpectedChar(char);
 
@@ -35,10 +37,10 @@ impl FromStr for BitInput {
 }
 
 #[allow(dead_code)]
-fn print_values(values: &[usize]) {
-    println!("values:");
+fn print_values(values: &[usize], diag: &Diag) {
+    debug!(diag, "values:");
     for v in values.iter() {
-        println!("  {:05b}", v);
+        debug!(diag, "  {:05b}", v);
     }
 }
 
@@ -57,7 +59,7 @@ fn get_bit_counts(values: &[BitInput]) -> HashMap<usize, usize> {
                 *count += 1;
             }
             // shift the value and move to the next bit
-            val = val >> 1;
+            val >>= 1;
             bit += 1;
         }
     }
@@ -65,19 +67,21 @@ fn get_bit_counts(values: &[BitInput]) -> HashMap<usize, usize> {
     bit_counts
 }
 
-pub fn calculate_gamma_epsilon(values: &[BitInput]) -> (usize, usize) {
-    assert!(values.len() > 0, "`values` must be non-empty slice");
-
+/// Gamma and epsilon rates, `None` if some bit is 0 and 1 equally often
+pub fn calculate_gamma_epsilon(values: &[BitInput]) -> Option<(usize, usize)> {
     // we now have counts of 1s for each bit position, number of 0s is total minus 1s
     let bit_counts = get_bit_counts(values);
     let total_count = values.len();
-    let n_bits = bit_counts.keys().max().unwrap() + 1;
+    let n_bits = bit_counts.keys().max().map_or(0, |bit| bit + 1);
 
     let mut gamma = 0;
     for bit in 0..n_bits {
         let n_ones = bit_counts[&bit];
         let n_zeros = total_count - n_ones;
-        assert!(n_ones != n_zeros, "What to do if there is the same number of 0s and 1s?");
+        // no way to tell which bit is the most common
+        if n_ones == n_zeros {
+            return None;
+        }
         if n_ones > n_zeros {
             // gamma uses the most common bit, so if it's 1 than we need to OR
             // we again need to use reversed (LSB first order)
@@ -89,32 +93,37 @@ pub fn calculate_gamma_epsilon(values: &[BitInput]) -> (usize, usize) {
     let mask = (1 << n_bits) - 1;
     let epsilon = !gamma & mask;
 
-    (gamma, epsilon)
+    Some((gamma, epsilon))
 }
 
 pub trait BitCriteria {
     fn keep(&self, value: usize, tested_bit: usize, n_ones: usize, n_zeros: usize) -> bool;
 }
 
-pub fn calculate_rating(values: &[BitInput], bit_criteria: impl BitCriteria) -> usize
+/// Rating of the single value left by the criteria, `None` if there is not exactly one
+pub fn calculate_rating(values: &[BitInput], bit_criteria: impl BitCriteria) -> Option<usize>
 {
     // needed to reverse the number ...
-    let n_bits = get_bit_counts(&values).keys().max().unwrap() + 1;
+    let n_bits = get_bit_counts(values).keys().max().map_or(0, |bit| bit + 1);
 
-    let mut values: Vec<_> = values.iter().cloned().collect();
+    let mut values: Vec<_> = values.to_vec();
     let mut bit = 0;
 
     // "if you have one number left, stop"
     while values.len() > 1 {
         // println!("bit = {}", bit);
+        // equal values cannot be told apart
+        if bit >= n_bits {
+            return None;
+        }
 
         let bit_counts = get_bit_counts(values.as_slice());
-        let n_ones = *bit_counts.get(&bit).expect("Infinite loop?");
+        let n_ones = bit_counts.get(&bit).copied().unwrap_or(0);
         let n_zeros = values.len() - n_ones;
 
         values = values.iter()
-            .cloned()
             .filter(|val| bit_criteria.keep(val.0, bit, n_ones, n_zeros))
+            .cloned()
             .collect();
 
         // values = values.iter()
@@ -132,15 +141,16 @@ pub fn calculate_rating(values: &[BitInput], bit_criteria: impl BitCriteria) ->
     }
 
     // now we need to reverse bits...
+    let value = values.first()?.0;
     let mut result = 0;
     for bit in 0..n_bits {
-        let bit_val = ((1 << bit) & values[0].0) != 0;
+        let bit_val = ((1 << bit) & value) != 0;
         if bit_val {
             result |= 1 << (n_bits - 1 - bit);
         }
     }
 
-    result
+    Some(result)
 }
 
 pub struct OxygenGenerator;
@@ -170,3 +180,41 @@ impl BitCriteria for CO2Scrubber {
     }
 }
 
+
+pub struct Day3;
+
+impl Solver for Day3 {
+    type Input = Vec<BitInput>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Binary Diagnostic",
+        part_1: "Power consumption, product of the gamma and epsilon rates",
+        part_2: "Life support rating, product of the oxygen generator and CO2 scrubber ratings",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        let vals: Vec<_> = utils::parse_str_all(input)?;
+        if vals.is_empty() {
+            Err(ParseError::new("No diagnostic numbers"))?;
+        }
+        Ok(vals)
+    }
+
+    fn part_1(&self, vals: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let (gamma, epsilon) = calculate_gamma_epsilon(vals)
+            .ok_or_else(|| anyhow::anyhow!("Some bit is 0 and 1 equally often"))?;
+        info!(ctx.diag, "gamma rate   = {0:3} = 0b{0:05b}", gamma);
+        info!(ctx.diag, "epsilon rate = {0:3} = 0b{0:05b}", epsilon);
+        Ok((gamma * epsilon).to_string())
+    }
+
+    fn part_2(&self, vals: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let oxygen = calculate_rating(vals, OxygenGenerator)
+            .ok_or_else(|| anyhow::anyhow!("No single oxygen generator rating"))?;
+        let co2_scrubber = calculate_rating(vals, CO2Scrubber)
+            .ok_or_else(|| anyhow::anyhow!("No single CO2 scrubber rating"))?;
+        info!(ctx.diag, "oxygen generator rating = {}", oxygen);
+        info!(ctx.diag, "CO2 scrubber rating     = {}", co2_scrubber);
+        Ok((oxygen * co2_scrubber).to_string())
+    }
+}
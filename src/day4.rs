@@ -1,8 +1,8 @@
-use std::{num::ParseIntError, fmt::Display, collections::HashSet};
+use std::{num::ParseIntError, fmt::Display, collections::HashSet, str::FromStr};
 
 use thiserror::Error;
 
-use aoc::columns::ColumnDisplay;
+use aoc::{info, debug, diag::Diag, columns::ColumnDisplay, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer}};
 
 #[derive(Debug)]
 pub struct BingoNumbers(Vec<usize>);
@@ -10,7 +10,7 @@ pub struct BingoNumbers(Vec<usize>);
 const BOARD_SIZE: usize = 5;
 type Board<T> = [[T; BOARD_SIZE]; BOARD_SIZE];
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct BingoBoard {
     numbers: Board<usize>,
     marks: Board<bool>,
@@ -44,7 +44,7 @@ impl BingoBoard {
             }
         }
         for col in 0..BOARD_SIZE {
-            if self.marks.iter().map(|row| row[col]).all(|marked| marked) {
+            if self.marks.iter().all(|row| row[col]) {
                 return true;
             }
         }
@@ -78,14 +78,12 @@ impl BingoBoard {
     // }
 }
 
-fn play<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], verbose: bool, until_n_winners: usize) -> Option<usize> {
+fn play<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], diag: &Diag, until_n_winners: usize) -> Option<usize> {
     let mut winners_set = HashSet::new();
 
     for (i, num) in nums.0.iter().enumerate() {
-        if verbose {
-            println!("Number {} (iter #{}):\n{}",
-                num, i, boards.column_display("  ".to_string(), "     ".to_string()));
-        }
+        debug!(diag, "Number {} (iter #{}):\n{}",
+            num, i, boards.column_display("  ".to_string(), "     ".to_string()));
         for board in boards.iter_mut() {
             board.mark(*num);
         }
@@ -109,11 +107,9 @@ fn play<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], verbose: bool,
 
                 let winner = &boards[i];
                 let score = winner.score(*num);
-                if verbose {
-                    println!("Found the board {}!", i);
-                    println!("Final state:\n{}",
-                        boards.column_display("  ".to_string(), "     ".to_string()));
-                }
+                info!(diag, "Found the board {}!", i);
+                info!(diag, "Final state:\n{}",
+                    boards.column_display("  ".to_string(), "     ".to_string()));
                 return Some(score);
             }
         }
@@ -122,12 +118,12 @@ fn play<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], verbose: bool,
     None
 }
 
-pub fn play_to_win<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], verbose: bool) -> Option<usize> {
-    play(nums, boards, verbose, 1)
+pub fn play_to_win<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], diag: &Diag) -> Option<usize> {
+    play(nums, boards, diag, 1)
 }
 
-pub fn play_to_loose<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], verbose: bool) -> Option<usize> {
-    play(nums, boards, verbose, boards.len())
+pub fn play_to_loose<'a>(nums: &'a BingoNumbers, boards: &'a mut [BingoBoard], diag: &Diag) -> Option<usize> {
+    play(nums, boards, diag, boards.len())
 }
 
 impl Display for BingoBoard {
@@ -150,7 +146,7 @@ impl Display for BingoBoard {
                     format!("{}{:width$}{}", start, num, end, width = width)
                 }).collect();
             if row != 0 {
-                writeln!(f, "")?;
+                writeln!(f)?;
             }
             write!(f, "{}", cols.join(" "))?;
         }
@@ -161,58 +157,98 @@ impl Display for BingoBoard {
 
 #[derive(Error, Debug)]
 pub enum InputError {
-    #[error("Not enough input lines")]
+    #[error("No bingo numbers")]
     MissingInput,
-    #[error("No bingo numbers order (maybe missing ',' ?)")]
-    NoInputNumbers,
-    // #[error("Number of lines with board values not divisible by {}: {0}", BOARD_SIZE)]
-    #[error("Wrong number of rows for last board lines: {0}")]
+    #[error("Bingo numbers should be on a single line separated by ','")]
+    MultilineNumbers,
+    #[error("No bingo boards")]
+    NoBoards,
+    #[error("Expected {} rows of a board, got {0}", BOARD_SIZE)]
     WrongNumberOfRows(usize),
-    #[error("Number of lines with board values not divisible by 5: {0}")]
+    #[error("Expected {} numbers in a row, got {0}", BOARD_SIZE)]
     WrongNumberOfColumns(usize),
-    #[error("Invalid number")]
+    #[error("Invalid number: {0}")]
     ParseNumError(#[from] ParseIntError),
 }
 
-/// Parse a list of non-empty lines into bingo task inputs
-pub fn parse_lines<S: AsRef<str>>(lines: &[S]) -> Result<(BingoNumbers, Vec<BingoBoard>), InputError> {
-    if lines.len() < 1 {
-        return Err(InputError::MissingInput);
-    }
+impl FromStr for BingoNumbers {
+    type Err = ParseError;
 
-    let nums = BingoNumbers(lines[0].as_ref()
-        .split(',')
-        .map(|num| num.parse()
-            .map_err(|e| InputError::from(e)))
-        .collect::<Result<Vec<_>, InputError>>()?
-    );
-    if nums.0.len() == 0 {
-        return Err(InputError::NoInputNumbers);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(line) = s.lines().nth(1) {
+            return Err(ParseError::new(InputError::MultilineNumbers).line(2, line));
+        }
+        s.split(',')
+            .map(|num| num.trim().parse()
+                .map_err(|e| ParseError::new(InputError::from(e)).at(1, s, num)))
+            .collect::<ParseResult<_>>()
+            .map(BingoNumbers)
     }
+}
 
-    let board_lines = lines[1..].chunks_exact(BOARD_SIZE);
-    if !board_lines.remainder().is_empty() {
-        return Err(InputError::WrongNumberOfRows(board_lines.remainder().len()));
-    }
+impl FromStr for BingoBoard {
+    type Err = ParseError;
 
-    let mut boards = Vec::with_capacity(board_lines.len());
-    for lines in board_lines {
-        assert_eq!(lines.len(), BOARD_SIZE);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = s.lines().collect();
+        if lines.len() != BOARD_SIZE {
+            return Err(ParseError::new(InputError::WrongNumberOfRows(lines.len())).line(1, lines[0]));
+        }
 
         let mut board = BingoBoard::default();
         for (row, line) in lines.iter().enumerate() {
-            let tokens: Vec<_> = line.as_ref().split_whitespace().collect();
+            let tokens: Vec<_> = line.split_whitespace().collect();
             if tokens.len() != BOARD_SIZE {
-                return Err(InputError::WrongNumberOfColumns(tokens.len()));
+                return Err(ParseError::new(InputError::WrongNumberOfColumns(tokens.len())).line(row + 1, line));
             }
             for (col, token) in tokens.iter().enumerate() {
-                let num = token.parse()?;
-                board.numbers[row][col] = num;
+                board.numbers[row][col] = token.parse()
+                    .map_err(|e| ParseError::new(InputError::from(e)).at(row + 1, line, token))?;
             }
         }
-
-        boards.push(board);
+        Ok(board)
     }
+}
 
+/// Parse bingo numbers and boards, all separated by blank lines
+pub fn parse_input(input: &str) -> ParseResult<(BingoNumbers, Vec<BingoBoard>)> {
+    let mut sections = utils::sections(input);
+    let nums = sections.next()
+        .ok_or_else(|| ParseError::new(InputError::MissingInput))?
+        .parse()?;
+    let boards: Vec<_> = sections
+        .map(|board| board.parse())
+        .collect::<ParseResult<_>>()?;
+    if boards.is_empty() {
+        return Err(ParseError::new(InputError::NoBoards));
+    }
     Ok((nums, boards))
 }
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = (BingoNumbers, Vec<BingoBoard>);
+
+    const DESCRIPTION: Description = Description {
+        title: "Giant Squid",
+        part_1: "Score of the first bingo board to win",
+        part_2: "Score of the last bingo board to win",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part_1(&self, (nums, boards): &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let score = play_to_win(nums, &mut boards.clone(), &ctx.diag)
+            .ok_or_else(|| anyhow::anyhow!("No board wins with the drawn numbers"))?;
+        Ok(score.to_string())
+    }
+
+    fn part_2(&self, (nums, boards): &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let score = play_to_loose(nums, &mut boards.clone(), &ctx.diag)
+            .ok_or_else(|| anyhow::anyhow!("Not all boards win with the drawn numbers"))?;
+        Ok(score.to_string())
+    }
+}
@@ -1,7 +1,7 @@
 use core::fmt;
-use std::{str::FromStr, num::ParseIntError, collections::HashMap, fmt::Display};
+use std::{str::FromStr, collections::HashMap, fmt::Display};
 
-use thiserror::Error;
+use aoc::{debug, diag::Diag, error::ParseError, utils, solver::{Solver, Description, Context, Answer}};
 
 #[derive(Debug, Clone)]
 pub struct Line {
@@ -28,7 +28,7 @@ impl Line {
     fn delta(&self) -> (usize, usize) {
         let dx = self.start.0 as isize - self.end.0 as isize;
         let dy = self.start.1 as isize - self.end.1 as isize;
-        (dx.abs() as usize, dy.abs() as usize)
+        (dx.unsigned_abs(), dy.unsigned_abs())
     }
 
     pub fn points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
@@ -94,7 +94,7 @@ impl Display for VentsCount {
 
         for y in 0 ..= max_y {
             if y != 0 {
-                writeln!(f, "")?;
+                writeln!(f)?;
             }
             for x in 0 ..= max_x {
                 let count = self.counts.get(&(x, y));
@@ -107,40 +107,43 @@ impl Display for VentsCount {
     }
 }
 
-#[derive(Error, Debug)]
-pub enum ParseLineError {
-    #[error("Malformed line: {0}")]
-    MalformedLine(String),
-    #[error("Malformed line part: {0}")]
-    MalformedPart(String),
-    #[error("Invalid number")]
-    ParseNumError(#[from] ParseIntError),
-}
-
 impl FromStr for Line {
-    type Err = ParseLineError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split("->");
-
-        let start = parts.next().ok_or(ParseLineError::MalformedLine(s.to_string()))?;
-        let end = parts.next().ok_or(ParseLineError::MalformedLine(s.to_string()))?;
-        if parts.next().is_some() {
-            return Err(ParseLineError::MalformedLine(s.to_string()));
-        }
+        let (x1, y1, x2, y2) = aoc::scan!(s, "{x1},{y1} -> {x2},{y2}", x1: usize, y1: usize, x2: usize, y2: usize)?;
+        Ok(Self { start: (x1, y1), end: (x2, y2) })
+    }
+}
 
-        let parse_part = |part: &str| -> Result<(usize, usize), ParseLineError> {
-            let mut nums = part.trim().split(',')
-                .map(|token| token.parse::<usize>().map_err(|e| ParseLineError::from(e)));
-            let x = nums.next().ok_or(ParseLineError::MalformedPart(part.to_string()))??;
-            let y = nums.next().ok_or(ParseLineError::MalformedPart(part.to_string()))??;
-            Ok((x, y))
-        };
 
-        let start = parse_part(start)?;
-        let end = parse_part(end)?;
+pub struct Day5;
 
-        Ok(Self { start, end })
+impl Day5 {
+    fn solve(vents: VentsCount, diag: &Diag) -> anyhow::Result<Answer> {
+        debug!(diag, "Hydrothermal vents:\n{}", vents);
+        Ok(vents.dangerous_area_count().to_string())
     }
 }
 
+impl Solver for Day5 {
+    type Input = Vec<Line>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Hydrothermal Venture",
+        part_1: "Number of points where at least two horizontal or vertical lines overlap",
+        part_2: "Number of points where at least two lines overlap, including diagonal ones",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(utils::parse_str_all(input)?)
+    }
+
+    fn part_1(&self, lines: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Self::solve(VentsCount::non_diagonal(lines), &ctx.diag)
+    }
+
+    fn part_2(&self, lines: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Self::solve(VentsCount::all(lines), &ctx.diag)
+    }
+}
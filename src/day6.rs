@@ -1,8 +1,17 @@
-pub fn load_data<S: AsRef<str>>(lines: &[S]) -> Vec<Lanternfish> {
-    lines[0].as_ref()
-        .split(',')
-        .map(|num| Lanternfish { timer: num.parse().unwrap() })
-        .collect()
+use anyhow::Result;
+
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer, Param}};
+
+pub fn load_data<S: AsRef<str>>(lines: &[S]) -> ParseResult<Vec<Lanternfish>> {
+    let (number, line) = utils::numbered_lines(lines).next()
+        .ok_or_else(|| ParseError::new("No lanternfish timers"))?;
+    let timers: Vec<usize> = utils::parse_comma_separated(line)
+        .map_err(|e| e.line(number, line))?;
+    let max = REPRODUCTION_PERIOD + FIRST_CYCLE_INCR - 1;
+    if let Some((token, timer)) = line.split(',').zip(&timers).find(|(_, timer)| **timer > max) {
+        return Err(ParseError::new(format!("Timer must be at most {}, got {}", max, timer)).at(number, line, token.trim()));
+    }
+    Ok(timers.into_iter().map(|timer| Lanternfish { timer }).collect())
 }
 
 #[derive(Debug, Clone)]
@@ -13,53 +22,17 @@ pub struct Lanternfish {
 const REPRODUCTION_PERIOD: usize = 7;
 const FIRST_CYCLE_INCR: usize = 2;
 
-impl Lanternfish {
-    pub fn new() -> Self {
-        Self { timer: REPRODUCTION_PERIOD + FIRST_CYCLE_INCR - 1 }
-    }
-
-    pub fn next_day(&mut self) -> Option<Lanternfish> {
-        match self.timer {
-            0 => {
-                self.timer = REPRODUCTION_PERIOD - 1;
-                Some(Lanternfish::new())
-            },
-            _ => {
-                self.timer -= 1;
-                None
-            }
-        }
-    }
-}
-
-pub fn simulate(mut fish: Vec<Lanternfish>, days: usize, verbose: bool) -> Vec<Lanternfish> {
-    for day in 1..=days {
-        let mut new = fish.iter_mut()
-            .filter_map(Lanternfish::next_day)
-            .collect();
-        fish.append(&mut new);
-
-        if verbose {
-            let timers: Vec<_> = fish.iter().map(|f| f.timer.to_string()).collect();
-            println!("After {:2} days: {}", day, timers.as_slice().join(","))
-        }
-    }
-
-    fish
-}
-
-pub fn calculate_population(initial: &[Lanternfish], days: usize, verbose: bool) -> usize {
+/// Number of fish after given days, `None` if it does not fit in `u128`
+pub fn calculate_population(initial: &[Lanternfish], days: usize, diag: &Diag) -> Option<u128> {
     // Instead of creating objects we can just keep track of counts of fish with given timer value.
     // `counts[n]` is the number of fish that have the `timer` value of `n`.
-    let mut counts = [0usize; REPRODUCTION_PERIOD + 2];
+    let mut counts = [0u128; REPRODUCTION_PERIOD + 2];
 
     for fish in initial {
         counts[fish.timer] += 1;
     }
 
-    if verbose {
-        println!("Initial: {:?}", counts);
-    }
+    info!(diag, "Initial: {:?}", counts);
 
     for day in 1..=days {
         // the ones with timer=0 will create offspring
@@ -68,15 +41,51 @@ pub fn calculate_population(initial: &[Lanternfish], days: usize, verbose: bool)
         // Decrement the timers
         // Need to move the ones from last element because timer=0 goes to 6 not 8
         counts.rotate_left(1);
-        counts[REPRODUCTION_PERIOD - 1] += counts[REPRODUCTION_PERIOD + FIRST_CYCLE_INCR - 1];
+        counts[REPRODUCTION_PERIOD - 1] = counts[REPRODUCTION_PERIOD - 1]
+            .checked_add(counts[REPRODUCTION_PERIOD + FIRST_CYCLE_INCR - 1])?;
 
         // ...and add the offspring overwriting the already-moved fish
         counts[REPRODUCTION_PERIOD + FIRST_CYCLE_INCR - 1] = offspring;
 
-        if verbose {
-            println!("After day {} (+ {}) = {:?} #{}", day, offspring, counts, counts.iter().sum::<usize>());
-        }
+        info!(diag, "After day {} (+ {}) = {:?} #{}", day, offspring, counts, counts.iter().sum::<u128>());
     }
 
-    counts.iter().sum()
+    counts.iter().try_fold(0u128, |acc, n| acc.checked_add(*n))
+}
+
+pub struct Day6;
+
+impl Day6 {
+    fn solve(fish: &[Lanternfish], days: usize, diag: &Diag) -> Result<Answer> {
+        calculate_population(fish, days, diag)
+            .map(|n| n.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Population overflows after {} days", days))
+    }
+}
+
+impl Solver for Day6 {
+    type Input = Vec<Lanternfish>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Lanternfish",
+        part_1: "Number of lanternfish after `days_1` days",
+        part_2: "Number of lanternfish after `days_2` days",
+    };
+
+    const PARAMS: &'static [Param] = &[
+        Param { name: "days_1", default: "80", help: "Number of days simulated in part 1" },
+        Param { name: "days_2", default: "256", help: "Number of days simulated in part 2" },
+    ];
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok(load_data(&input.lines().collect::<Vec<_>>())?)
+    }
+
+    fn part_1(&self, fish: &Self::Input, ctx: &Context) -> Result<Answer> {
+        Self::solve(fish, ctx.param("days_1")?, &ctx.diag)
+    }
+
+    fn part_2(&self, fish: &Self::Input, ctx: &Context) -> Result<Answer> {
+        Self::solve(fish, ctx.param("days_2")?, &ctx.diag)
+    }
 }
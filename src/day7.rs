@@ -1,8 +1,12 @@
-pub fn load_data<S: AsRef<str>>(lines: &[S]) -> Vec<usize> {
-    lines[0].as_ref()
-        .split(',')
-        .map(|num| num.parse().unwrap())
-        .collect()
+use anyhow::Result;
+
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, Answer}};
+
+pub fn load_data<S: AsRef<str>>(lines: &[S]) -> ParseResult<Vec<usize>> {
+    let (number, line) = utils::numbered_lines(lines).next()
+        .ok_or_else(|| ParseError::new("No crab positions"))?;
+    utils::parse_comma_separated(line)
+        .map_err(|e| e.line(number, line))
 }
 
 #[allow(dead_code)]
@@ -10,9 +14,9 @@ fn median(nums: &[usize]) -> usize {
     let mut nums = nums.to_vec();
     nums.sort();
     let n = nums.len();
-    let mid = &nums[n / 2 ..= (n + 1) / 2];
+    let mid = &nums[n / 2 ..= n.div_ceil(2)];
     let n = mid.len();
-    let sum = mid.into_iter()
+    let sum = mid.iter()
         .map(|v| *v as f32)
         .sum::<f32>();
     (sum / n as f32).round() as usize
@@ -31,7 +35,7 @@ pub enum FuelCost {
 }
 
 fn fuel_cost(crabs: &[usize], pos: usize, rate: FuelCost) -> usize {
-    let abs_diff = |a, b| if a > b { a - b } else { b - a };
+    let abs_diff = |a: usize, b: usize| a.abs_diff(b);
     let diffs = crabs.iter().map(|p| abs_diff(pos, *p));
     match rate {
         FuelCost::Constant => diffs.sum(),
@@ -68,3 +72,35 @@ pub fn best_position(crabs: &[usize], rate: FuelCost) -> BestResult {
     let pos = brute_force;
     BestResult { pos, cost: fuel_cost(crabs, pos, rate) }
 }
+
+pub struct Day7;
+
+impl Day7 {
+    fn solve(crabs: &[usize], rate: FuelCost, diag: &Diag) -> Result<Answer> {
+        let best = best_position(crabs, rate);
+        info!(diag, "position = {}, cost = {}", best.pos, best.cost);
+        Ok(best.cost.to_string())
+    }
+}
+
+impl Solver for Day7 {
+    type Input = Vec<usize>;
+
+    const DESCRIPTION: Description = Description {
+        title: "The Treachery of Whales",
+        part_1: "Least fuel needed to align the crabs when each step costs 1",
+        part_2: "Least fuel needed to align the crabs when each step costs 1 more than the previous one",
+    };
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok(load_data(&input.lines().collect::<Vec<_>>())?)
+    }
+
+    fn part_1(&self, crabs: &Self::Input, ctx: &Context) -> Result<Answer> {
+        Self::solve(crabs, FuelCost::Constant, &ctx.diag)
+    }
+
+    fn part_2(&self, crabs: &Self::Input, ctx: &Context) -> Result<Answer> {
+        Self::solve(crabs, FuelCost::Increasing, &ctx.diag)
+    }
+}
@@ -1,5 +1,7 @@
 use std::{str::FromStr, fmt::Display};
 
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, utils, solver::{Solver, Description, Context, SolverError, Answer, Part}};
+
 
 /// 7-segment display segements' states
 ///
@@ -111,21 +113,21 @@ pub struct InputRecord {
     pub task: Vec<Segments>,
 }
 
-pub fn load_data<S: AsRef<str>>(lines: &[S]) -> Vec<InputRecord> {
-    lines.iter()
-        .map(|line| {
-            let sides = line.as_ref()
-             .split('|')
-             .map(str::trim)
-             .map(|sides| sides.split_whitespace()
-                  .map(|token| token.parse::<Segments>().unwrap())
-                  .collect::<Vec<Segments>>()
-              ).collect::<Vec<_>>();
-             assert_eq!(sides.len(), 2, "There should be 2 sides delimited by |, but got {}", sides.len());
-             InputRecord {
-                 observations: sides[0].clone(),
-                 task: sides[1].clone(),
-             }
+pub fn load_data<S: AsRef<str>>(lines: &[S]) -> ParseResult<Vec<InputRecord>> {
+    utils::numbered_lines(lines)
+        .map(|(number, line)| {
+            let parse_side = |side: &str| side.split_whitespace()
+                .map(|token| token.parse::<Segments>()
+                    .map_err(|e| ParseError::new(e).at(number, line, token)))
+                .collect::<ParseResult<Vec<_>>>();
+            match line.split('|').collect::<Vec<_>>()[..] {
+                [observations, task] => Ok(InputRecord {
+                    observations: parse_side(observations)?,
+                    task: parse_side(task)?,
+                }),
+                ref sides => Err(ParseError::new(format!("Expected 2 sides delimited by |, got {}", sides.len()))
+                    .line(number, line)),
+            }
         }).collect()
 }
 
@@ -135,7 +137,7 @@ impl InputRecord {
         // find digits that have the same number of segments as this one
         let same_count = segments.same_count_digits();
 
-        if same_count.len() == 0 || same_count.len() > 1 {
+        if same_count.len() != 1 {
             Err(())
         } else {
             Ok(same_count[0])
@@ -164,7 +166,7 @@ pub fn solve_part1(lines: &[InputRecord]) -> usize {
         }).sum()
 }
 
-pub fn print_lines(lines: &[InputRecord]) {
+pub fn print_lines(lines: &[InputRecord], diag: &Diag) {
     for line in lines {
         let fmt_part = |digits: &[Segments]| {
             digits.iter()
@@ -172,6 +174,31 @@ pub fn print_lines(lines: &[InputRecord]) {
                 .collect::<Vec<_>>()
                 .join(" ")
         };
-        println!("{} | {}", fmt_part(&line.observations), fmt_part(&line.task));
+        info!(diag, "{} | {}", fmt_part(&line.observations), fmt_part(&line.task));
+    }
+}
+
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Vec<InputRecord>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Seven Segment Search",
+        part_1: "Number of times digits 1, 4, 7 or 8 appear in the output values",
+        part_2: "Sum of all decoded output values",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(load_data(&input.lines().collect::<Vec<_>>())?)
+    }
+
+    fn part_1(&self, lines: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        print_lines(lines, &ctx.diag);
+        Ok(solve_part1(lines).to_string())
+    }
+
+    fn part_2(&self, _lines: &Self::Input, _ctx: &Context) -> anyhow::Result<Answer> {
+        Err(SolverError::Unimplemented(Part::Two).into())
     }
 }
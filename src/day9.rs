@@ -1,28 +1,13 @@
 use std::collections::HashSet;
 
-pub fn load_data<S: AsRef<str>>(lines: &[S]) -> Heights {
-    let grid = lines.iter()
-        .map(|line| {
-            line.as_ref()
-                .chars()
-                .map(|c| c.to_string()
-                     .parse()
-                     .unwrap())
-                .collect()
-        }).collect();
-    Heights { grid }
-}
+use aoc::{info, diag::{Diag, Level}, error::ParseResult, utils, grid::{Grid, BOLD}, solver::{Solver, Description, Context, Answer}};
 
-pub struct Heights {
-    grid: Vec<Vec<u8>>,
+pub fn load_data(input: &str) -> ParseResult<Heights> {
+    Ok(Heights { grid: utils::parse_digit_grid(input)? })
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Side {
-    Left,
-    Right,
-    Up,
-    Down,
+pub struct Heights {
+    grid: Grid<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,36 +25,18 @@ impl From<(usize, usize, u8)> for Point {
 
 impl Heights {
     pub fn neighbours(&self, x: usize, y: usize) -> Vec<Point> {
-        let sides = [Side::Left, Side::Right, Side::Up, Side::Down];
-        sides.iter()
-            .filter_map(|side| self.neighbour(x, y, *side))
+        self.grid.neighbours((x, y), false)
+            .into_iter()
+            .map(|n| (n.x, n.y, n.value).into())
             .collect()
     }
 
-    fn neighbour(&self, x: usize, y: usize, side: Side) -> Option<Point> {
-        let (xn, yn) = match side {
-            Side::Left => x.checked_sub(1).map(|xsub| (xsub, y)),
-            Side::Right => Some((x + 1, y)),
-            Side::Up => y.checked_sub(1).map(|ysub| (x, ysub)),
-            Side::Down => Some((x, y + 1)),
-        }?;
-        self.grid.get(xn)
-            .and_then(|row| row.get(yn).copied())
-            .map(|height| (xn, yn, height).into())
-    }
-
-    fn iter_all<'a>(&'a self) -> impl Iterator<Item=Point> + 'a {
+    fn iter_all(&self) -> impl Iterator<Item=Point> + '_ {
         self.grid.iter()
-            .enumerate()
-            .flat_map(|(x, row)| {
-                row.iter()
-                    .enumerate()
-                    .map(move |(y, val)| (x, y, *val).into())
-                    // .inspect(|all| println!("{:?}", all))
-            })
+            .map(|p| (p.x, p.y, p.value).into())
     }
 
-    fn low_points<'a>(&'a self) -> impl Iterator<Item=Point> + 'a {
+    fn low_points(&self) -> impl Iterator<Item=Point> + '_ {
         self.iter_all()
             .filter(|p| {
                 self.neighbours(p.x, p.y).iter()
@@ -84,51 +51,31 @@ impl Heights {
         to_visit.push((x, y));
         while let Some((x, y)) = to_visit.pop() {
             visited.insert((x, y));
-            let height = self.grid[x][y];
+            let height = self.grid[(x, y)];
             to_visit.extend(
                 self.neighbours(x, y)
                     .iter()
                     .filter(|n| n.height >= height && n.height < 9)
                     .map(|n| (n.x, n.y))
-                    .filter(|pos| visited.get(pos).is_none())
+                    .filter(|pos| !visited.contains(pos))
             );
         }
         visited.into_iter()
-            .map(|(x, y)| (x, y, self.grid[x][y]).into())
+            .map(|(x, y)| (x, y, self.grid[(x, y)]).into())
             .collect()
     }
 
-    fn show(&self, highlighted: &HashSet<(usize, usize)>) {
-        for (x, row) in self.grid.iter().enumerate() {
-            for (y, col) in row.iter().enumerate() {
-                let bold = "\x1b[1m";
-                let clear = "\x1b[0m";
-                let maybe = |val| {
-                    if highlighted.get(&(x, y)).is_some() {
-                        val
-                    } else {
-                        ""
-                    }
-                };
-                // let s = if *col == 9 {
-                //     ".".to_string()
-                // } else {
-                //     col.to_string()
-                // };
-                let s = col.to_string();
-                print!("{}{}{}", maybe(bold), s, maybe(clear));
-            }
-            println!();
-        }
+    fn show(&self, highlighted: &HashSet<(usize, usize)>, diag: &Diag) {
+        info!(diag, "{}", self.grid.to_string(BOLD, |pos| highlighted.contains(&pos), |height| height.to_string()));
     }
 }
 
-pub fn part_1(heights: &Heights, verbose: bool) -> usize {
-    if verbose {
+pub fn part_1(heights: &Heights, diag: &Diag) -> usize {
+    if diag.enabled(Level::Info) {
         let highlighted = heights.low_points()
             .map(|p| (p.x, p.y))
             .collect();
-        heights.show(&highlighted);
+        heights.show(&highlighted, diag);
     }
     let risk_levels = heights.low_points()
         // .inspect(|(x, y, height)| {
@@ -144,22 +91,48 @@ pub fn part_1(heights: &Heights, verbose: bool) -> usize {
     risk_levels.sum()
 }
 
-pub fn part_2(heights: &Heights, verbose: bool) -> usize {
+/// Product of sizes of the three largest basins, `None` if there are fewer basins
+pub fn part_2(heights: &Heights, diag: &Diag) -> Option<usize> {
     let mut basins: Vec<_> = heights.low_points()
         .map(|p| heights.basin(p.x, p.y))
         .collect();
     basins.sort_unstable_by_key(|b| b.len());
-    let largest3 = &basins[(basins.len() - 3)..];
-    assert_eq!(largest3.len(), 3);
-    if verbose {
+    let largest3 = &basins[basins.len().checked_sub(3)?..];
+    if diag.enabled(Level::Info) {
         let mut highlighted = HashSet::new();
         for b in largest3 {
             highlighted.extend(b.iter().map(|p| (p.x, p.y)));
         }
-        heights.show(&highlighted);
+        heights.show(&highlighted, diag);
     }
     let result = largest3.iter()
         .map(|b| b.len())
         .product();
-    result
+    Some(result)
+}
+
+pub struct Day9;
+
+impl Solver for Day9 {
+    type Input = Heights;
+
+    const DESCRIPTION: Description = Description {
+        title: "Smoke Basin",
+        part_1: "Sum of risk levels of all low points",
+        part_2: "Product of sizes of the three largest basins",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(load_data(input)?)
+    }
+
+    fn part_1(&self, heights: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        Ok(part_1(heights, &ctx.diag).to_string())
+    }
+
+    fn part_2(&self, heights: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        part_2(heights, &ctx.diag)
+            .map(|product| product.to_string())
+            .ok_or_else(|| anyhow::anyhow!("Fewer than three basins"))
+    }
 }
@@ -0,0 +1,149 @@
+use std::{io::{self, Write}, sync::{Arc, Mutex}, fs::File, path::Path, fmt};
+
+/// How much diagnostic output is wanted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    #[default]
+    Off,
+    /// Enabled with `-v`
+    Info,
+    /// Enabled with `-vv`, for large dumps
+    Debug,
+}
+
+impl Level {
+    /// Level for the number of `-v` flags
+    pub fn from_verbosity(n: u64) -> Self {
+        match n {
+            0 => Level::Off,
+            1 => Level::Info,
+            _ => Level::Debug,
+        }
+    }
+}
+
+/// In-memory destination of diagnostics
+#[derive(Debug, Clone, Default)]
+pub struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sink for diagnostics of solvers, kept separate from the answers
+///
+/// Cloning is cheap and all clones write to the same destination.
+/// Use the `info!` and `debug!` macros to write to it.
+#[derive(Clone)]
+pub struct Diag {
+    level: Level,
+    out: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl Diag {
+    pub fn stderr(level: Level) -> Self {
+        Self { level, out: Arc::new(Mutex::new(io::stderr())) }
+    }
+
+    pub fn file(path: impl AsRef<Path>, level: Level) -> io::Result<Self> {
+        Ok(Self { level, out: Arc::new(Mutex::new(File::create(path)?)) })
+    }
+
+    /// Collect diagnostics in memory, e.g. to inspect them in tests
+    pub fn buffer(level: Level) -> (Self, Buffer) {
+        let buffer = Buffer::default();
+        (Self { level, out: Arc::new(Mutex::new(buffer.clone())) }, buffer)
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn enabled(&self, level: Level) -> bool {
+        level != Level::Off && level <= self.level
+    }
+
+    /// Write a line, errors of the destination are ignored
+    pub fn log(&self, args: fmt::Arguments) {
+        let mut out = self.out.lock().unwrap();
+        let _ = writeln!(out, "{}", args);
+    }
+
+    /// Copy diagnostics collected in a buffer, e.g. by another thread
+    pub fn append(&self, buffer: &Buffer) {
+        let contents = buffer.0.lock().unwrap();
+        if !contents.is_empty() {
+            let _ = self.out.lock().unwrap().write_all(&contents);
+        }
+    }
+}
+
+impl Default for Diag {
+    fn default() -> Self {
+        Self::stderr(Level::Off)
+    }
+}
+
+impl fmt::Debug for Diag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Diag").field("level", &self.level).finish()
+    }
+}
+
+/// Write a line to a `Diag` if it has at least given level
+///
+/// The arguments are only evaluated when the level is enabled.
+#[macro_export]
+macro_rules! diag {
+    ($diag:expr, $level:expr, $($arg:tt)*) => {
+        if $diag.enabled($level) {
+            $diag.log(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Write a line shown with `-v`
+#[macro_export]
+macro_rules! info {
+    ($diag:expr, $($arg:tt)*) => { $crate::diag!($diag, $crate::diag::Level::Info, $($arg)*) };
+}
+
+/// Write a line shown only with `-vv`
+#[macro_export]
+macro_rules! debug {
+    ($diag:expr, $($arg:tt)*) => { $crate::diag!($diag, $crate::diag::Level::Debug, $($arg)*) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        let (diag, buffer) = Diag::buffer(Level::Info);
+        info!(diag, "shown {}", 1);
+        debug!(diag, "hidden {}", 2);
+        assert_eq!(buffer.contents(), "shown 1\n");
+    }
+
+    #[test]
+    fn off_skips_arguments() {
+        let (diag, buffer) = Diag::buffer(Level::Off);
+        let mut calls = 0;
+        info!(diag, "{}", { calls += 1; calls });
+        assert_eq!(calls, 0);
+        assert_eq!(buffer.contents(), "");
+    }
+}
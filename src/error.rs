@@ -0,0 +1,143 @@
+use std::{error::Error, fmt::Display};
+
+/// Invalid puzzle input, pointing at the offending text
+///
+/// Loaders know the position within the input, the day is filled in by the runner.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseError {
+    pub day: Option<usize>,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// 1-based column, counted in characters
+    pub column: Option<usize>,
+    /// The offending line
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(reason: impl Display) -> Self {
+        Self { reason: reason.to_string(), ..Self::default() }
+    }
+
+    /// Error in given line, `number` is 1-based
+    pub fn line(mut self, number: usize, text: &str) -> Self {
+        self.line = Some(number);
+        self.text = text.to_string();
+        self
+    }
+
+    /// Set 1-based column within the line
+    pub fn column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    /// Error at `token` in given line, `token` should be a slice of `text`
+    pub fn at(self, number: usize, text: &str, token: &str) -> Self {
+        let mut error = self.within(text, token);
+        error.line = Some(number);
+        error
+    }
+
+    /// Error at `token` in a line whose number is not known yet
+    pub fn within(mut self, text: &str, token: &str) -> Self {
+        let offset = (token.as_ptr() as usize).checked_sub(text.as_ptr() as usize)
+            .filter(|offset| offset + token.len() <= text.len())
+            .or_else(|| text.find(token));
+        self.text = text.to_string();
+        self.column = offset.map(|offset| text[..offset].chars().count() + 1);
+        self
+    }
+
+    /// Error of parsing given line, keeps the column if `error` is a `ParseError` itself
+    pub fn in_line(number: usize, text: &str, error: Box<dyn Error + Send + Sync>) -> Self {
+        match error.downcast::<ParseError>() {
+            Ok(error) => Self { line: Some(number), text: text.to_string(), ..*error },
+            Err(error) => Self::new(error).line(number, text),
+        }
+    }
+
+    pub fn day(mut self, day: usize) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    /// Multi-line description with the offending text and a marker below its column
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        if let Some(line) = self.line {
+            let number = line.to_string();
+            report += &format!("\n {} | {}", number, self.text);
+            if let Some(column) = self.column {
+                report += &format!("\n {} | {:>width$}", " ".repeat(number.len()), "^", width = column);
+            }
+        }
+        report
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let position: Vec<_> = [("Day", self.day), ("line", self.line), ("column", self.column)].iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{} {}", name, value)))
+            .collect();
+        if !position.is_empty() {
+            write!(f, "{}: ", position.join(", "))?;
+        }
+        write!(f, "{}", self.reason)?;
+        if !self.text.is_empty() {
+            write!(f, " in '{}'", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Errors of all invalid lines of an input, in order of lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl ParseErrors {
+    pub fn report(&self) -> String {
+        self.0.iter()
+            .map(ParseError::report)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0[..] {
+            [] => write!(f, "No errors"),
+            [error] => write!(f, "{}", error),
+            [first, rest @ ..] => write!(f, "{} (and {} more invalid lines)", first, rest.len()),
+        }
+    }
+}
+
+impl Error for ParseErrors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let line = "6,x10";
+        let e = ParseError::new("Invalid number").at(3, line, &line[2..]).day(13);
+        assert_eq!(e.to_string(), "Day 13, line 3, column 3: Invalid number in '6,x10'");
+        assert_eq!(e.report(), "Day 13, line 3, column 3: Invalid number in '6,x10'\n 3 | 6,x10\n   |   ^");
+    }
+
+    #[test]
+    fn token_not_in_line() {
+        let e = ParseError::new("Unknown fold").at(1, "fold along z=1", "z=1");
+        assert_eq!(e.column, Some(12));
+        assert_eq!(ParseError::new("Missing").at(1, "abc", "x").column, None);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{env, fs::{self, OpenOptions}, io::{self, Write}, path::{Path, PathBuf}};
+
+use anyhow::{Result, Context};
+use thiserror::Error;
+
+use crate::runner::{self, DEFAULT_INPUT};
+
+/// Year of the puzzles solved here
+pub const YEAR: usize = 2021;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Environment variable holding the session cookie
+const SESSION_VAR: &str = "AOC_SESSION";
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+    #[error("No session token, set {} or write it to {}", SESSION_VAR, .0.display())]
+    NoSession(PathBuf),
+    #[error("Input {} already exists", .0.display())]
+    Exists(PathBuf),
+    #[error("Request to {url} failed with status {status}: {body}")]
+    Status {
+        url: String,
+        status: u16,
+        body: String,
+    },
+}
+
+/// Config file with the session token, `~/.config/aoc/session`
+fn session_file() -> PathBuf {
+    let config = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .unwrap_or_default();
+    config.join("aoc").join("session")
+}
+
+/// Session token from the environment, or from the config file if not set
+pub fn session_token() -> Result<String> {
+    if let Some(token) = env::var(SESSION_VAR).ok().filter(|token| !token.trim().is_empty()) {
+        return Ok(token.trim().to_string());
+    }
+    let path = session_file();
+    match fs::read_to_string(&path) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        Ok(_) => Err(FetchError::NoSession(path).into()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(FetchError::NoSession(path).into()),
+        Err(e) => Err(e).with_context(|| format!("Cannot read {}", path.display())),
+    }
+}
+
+/// Authenticated access to the puzzle site
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: String) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(concat!("aoc/", env!("CARGO_PKG_VERSION")))
+            .build();
+        Self { base_url: base_url.trim_end_matches('/').to_string(), session, agent }
+    }
+
+    /// URL of given day's page, `path` is appended to it
+    pub fn day_url(&self, day: usize, path: &str) -> String {
+        format!("{}/{}/day/{}{}", self.base_url, YEAR, day, path)
+    }
+
+    /// Send a request with the session cookie, failing on any status but 200
+    pub fn send(&self, method: &str, url: &str, form: &[(&str, &str)]) -> Result<String> {
+        let request = self.agent.request(method, url)
+            .set("Cookie", &format!("session={}", self.session));
+        let response = if form.is_empty() { request.call() } else { request.send_form(form) };
+        match response {
+            Ok(response) => Ok(response.into_string()?),
+            Err(ureq::Error::Status(status, response)) => Err(FetchError::Status {
+                url: url.to_string(),
+                status,
+                body: response.into_string().unwrap_or_default().trim().to_string(),
+            }.into()),
+            Err(e) => Err(e).with_context(|| format!("Request to {} failed", url)),
+        }
+    }
+
+    /// Download personal puzzle input of given day
+    pub fn input(&self, day: usize) -> Result<String> {
+        self.send("GET", &self.day_url(day, "/input"), &[])
+    }
+}
+
+/// Download input of given day to `path`, existing files are left untouched
+pub fn fetch_to(client: &Client, day: usize, path: &Path) -> Result<()> {
+    // checked before downloading to not bother the server needlessly
+    if path.exists() {
+        Err(FetchError::Exists(path.to_path_buf()))?;
+    }
+    let input = client.input(day)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => FetchError::Exists(path.to_path_buf()).into(),
+            _ => anyhow::Error::new(e).context(format!("Cannot create {}", path.display())),
+        })?;
+    file.write_all(input.as_bytes())?;
+    Ok(())
+}
+
+/// Download input of given day to `data/NN/task`
+pub fn fetch(client: &Client, day: usize) -> Result<PathBuf> {
+    let path = runner::input_path(day, DEFAULT_INPUT);
+    fetch_to(client, day, &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::{net::TcpListener, io::{BufRead, BufReader, Read}, thread::{self, JoinHandle}};
+
+    /// Stand-in server answering a single request, returns the request line and headers
+    pub fn stand_in(status: &'static str, body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            let mut form = vec![0; length];
+            reader.read_exact(&mut form).unwrap();
+            request.push_str(&String::from_utf8(form).unwrap());
+            write!(&stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+            request
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn downloads_once() {
+        let path = env::temp_dir().join(format!("aoc-fetch-{}", std::process::id())).join("task");
+        let (url, server) = stand_in("200 OK", "1\n2\n3\n");
+        let client = Client::new(&url, "secret".to_string());
+        fetch_to(&client, 7, &path).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /2021/day/7/input "), "{}", request);
+        assert!(request.contains("session=secret"), "{}", request);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1\n2\n3\n");
+
+        // the stand-in is gone, so a second request would fail differently
+        let e = fetch_to(&client, 7, &path).unwrap_err();
+        assert!(matches!(e.downcast_ref(), Some(FetchError::Exists(_))), "{}", e);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_status() {
+        let path = env::temp_dir().join(format!("aoc-fetch-failed-{}", std::process::id()));
+        let (url, _) = stand_in("400 Bad Request", "Puzzle inputs differ by user.");
+        let e = fetch_to(&Client::new(&url, "expired".to_string()), 7, &path).unwrap_err();
+        assert!(matches!(e.downcast_ref(), Some(FetchError::Status { status: 400, .. })), "{}", e);
+        assert!(!path.exists());
+    }
+}
@@ -176,7 +176,7 @@ impl<T: Copy> Grid<T> {
                     .iter()
                     .filter(|p| condition(pos, p))
                     .map(|n| (n.x, n.y))
-                    .filter(|pos| visited.get(pos).is_none())
+                    .filter(|pos| !visited.contains(pos))
             );
         }
         visited.into_iter()
@@ -236,7 +236,7 @@ mod tests {
             4, 5, 6,
             7, 8, 9u8
         ];
-        Grid::from(vals.into_iter(), 3).unwrap()
+        Grid::from(vals, 3).unwrap()
     }
 
     fn values(neighbours: Vec<GridPoint<u8>>) -> Vec<u8> {
@@ -0,0 +1,111 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use regex::{Regex, Captures};
+use structopt::clap::{App, Shell};
+
+use crate::{registry, runner};
+
+/// Print what the puzzle of given day is about, its inputs and parameters
+pub fn print_day(day: usize) -> Result<()> {
+    let solver = registry::get(day).ok_or(crate::DayError(day))?;
+    let description = solver.description();
+
+    println!("Day {}: {}", day, description.title);
+    println!();
+    println!("Part 1: {}", description.part_1);
+    println!("Part 2: {}", description.part_2);
+    println!();
+
+    let inputs = runner::available_inputs(day);
+    if inputs.is_empty() {
+        println!("Inputs: none in {}", runner::input_path(day, "").display());
+    } else {
+        println!("Inputs: {}", inputs.join(", "));
+    }
+
+    let params = solver.params();
+    if !params.is_empty() {
+        println!();
+        println!("Parameters (--param key=value):");
+        let width = params.iter().map(|p| p.name.len() + p.default.len() + 1).max().unwrap_or(0);
+        for param in params {
+            let key = format!("{}={}", param.name, param.default);
+            println!("  {:<width$}  {}", key, param.help, width = width);
+        }
+    }
+
+    Ok(())
+}
+
+/// Day numbers and input names offered by shell completions
+struct Values {
+    days: Vec<String>,
+    inputs: Vec<String>,
+}
+
+impl Values {
+    fn collect() -> Self {
+        let days = registry::days().map(|day| day.to_string()).collect();
+        let mut inputs: Vec<_> = registry::days().flat_map(runner::available_inputs).collect();
+        inputs.sort();
+        inputs.dedup();
+        Self { days, inputs }
+    }
+}
+
+/// Add day numbers and input names to a script generated by clap
+///
+/// Clap only completes option names and subcommands, so the values of
+/// positional arguments are filled in for each shell separately.
+fn add_values(script: &str, shell: Shell, values: &Values) -> String {
+    let days = values.days.join(" ");
+    let inputs = values.inputs.join(" ");
+    match shell {
+        Shell::Bash => script
+            .replace("<days>", &format!("all {}", days))
+            .replace("<day>", &days)
+            .replace("<input>", &inputs),
+        Shell::Zsh => {
+            // positional arguments look like ':name -- help:_files', '::name' if optional
+            let positional = Regex::new(r"'(::?)(days|day|input)((?: -- [^']*)?):_files'").unwrap();
+            positional.replace_all(script, |caps: &Captures| {
+                let action = match &caps[2] {
+                    "days" => format!("(all {})", days),
+                    "day" => format!("({})", days),
+                    _ => format!(" _alternative \"names:input:({})\" \"files:file:_files\"", inputs),
+                };
+                format!("'{}{}{}:{}'", &caps[1], &caps[2], &caps[3], action)
+            }).to_string()
+        },
+        Shell::Fish => format!("{}\
+            complete -c aoc -n \"__fish_use_subcommand\" -a \"all {days}\" -d 'Day'\n\
+            complete -c aoc -n \"__fish_seen_subcommand_from bench help fetch submit\" -f -a \"{days}\" -d 'Day'\n\
+            complete -c aoc -a \"{inputs}\" -d 'Input'\n",
+            script, days = days, inputs = inputs),
+        _ => script.to_string(),
+    }
+}
+
+/// Write completion script for given shell to stdout
+pub fn print_completions(mut app: App, shell: Shell) -> Result<()> {
+    let name = app.get_name().to_string();
+    let mut script = Vec::new();
+    app.gen_completions_to(name, shell, &mut script);
+    let script = add_values(&String::from_utf8(script)?, shell, &Values::collect());
+    io::stdout().write_all(script.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zsh_values() {
+        let values = Values { days: vec!["1".into(), "2".into()], inputs: vec!["task".into()] };
+        let script = "'::days -- Day number:_files' \\\n':day:_files' \\\n'--log=[Log file]:_files' \\\n";
+        assert_eq!(add_values(script, Shell::Zsh, &values),
+            "'::days -- Day number:(all 1 2)' \\\n':day:(1 2)' \\\n'--log=[Log file]:_files' \\\n");
+    }
+}
@@ -1,4 +1,10 @@
+pub mod alloc;
+pub mod bench;
 pub mod columns;
+pub mod diag;
+pub mod error;
 // pub mod graph;
 pub mod grid;
+pub mod pool;
+pub mod solver;
 pub mod utils;
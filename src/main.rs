@@ -1,10 +1,25 @@
-use std::{path::PathBuf, error::Error, io, fmt::Display, fs::File};
+use std::{path::PathBuf, error::Error, fmt::Display, time::{Duration, Instant}, thread, num::NonZeroUsize, process};
 
-use structopt::StructOpt;
+use structopt::{StructOpt, clap};
 use anyhow::Result;
 
-use aoc::utils;
+use aoc::{solver::{Part, SolverError, ParamValue, Params}, diag::{Diag, Level}, alloc::CountingAllocator, error::{ParseError, ParseErrors}, pool};
 
+use registry::Selection;
+use runner::{Input, InputError, DayResult, RunConfig};
+use output::Format;
+
+mod registry;
+mod runner;
+mod check;
+mod output;
+mod watch;
+mod scaffold;
+mod help;
+mod repl;
+mod server;
+mod fetch;
+mod submit;
 mod day1;
 mod day2;
 mod day3;
@@ -23,12 +38,136 @@ mod day15;
 mod day16;
 mod day17;
 
+/// Counts allocations only when enabled with `--allocs`
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
 #[derive(Debug, StructOpt)]
+#[structopt(setting = clap::AppSettings::DisableHelpSubcommand)]
 struct Opt {
-    day: usize,
-    input: PathBuf,
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+    /// Day number, range of days (e.g. 1..=17) or "all"
+    days: Option<Selection>,
+    #[structopt(flatten)]
+    input: InputOpt,
+    /// Print diagnostics of solvers to stderr, -vv for more details
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u64,
+    /// Write diagnostics to given file instead of stderr
+    #[structopt(long)]
+    log: Option<PathBuf>,
+    /// Compare answers with the ones stored in data/NN/answers
+    #[structopt(long)]
+    check: bool,
+    /// Run only given part (1 or 2)
     #[structopt(short, long)]
-    verbose: bool,
+    part: Option<Part>,
+    /// Output format: text, json or csv
+    #[structopt(long, default_value = "text")]
+    format: Format,
+    /// Report time of parsing and of each part separately
+    #[structopt(long)]
+    stats: bool,
+    /// Count allocations, bytes allocated and peak live bytes of each phase (implies --stats)
+    #[structopt(long)]
+    allocs: bool,
+    /// Stop waiting for a part after given number of seconds and report TIMEOUT
+    #[structopt(long, parse(try_from_str = parse_seconds))]
+    timeout: Option<Duration>,
+    /// Number of days run in parallel, 0 to use all cores
+    #[structopt(short, long, default_value = "1")]
+    jobs: usize,
+    /// Re-run whenever the input file changes
+    #[structopt(long)]
+    watch: bool,
+    /// Override a puzzle parameter, e.g. days_2=500 for day 6 (can be repeated)
+    #[structopt(long = "param", number_of_values = 1)]
+    params: Vec<ParamValue>,
+}
+
+#[derive(Debug, StructOpt)]
+struct InputOpt {
+    /// Input name in data/NN/ (e.g. example2), a path to input file or - for stdin [default: task]
+    input: Option<String>,
+    /// Use given string as input
+    #[structopt(long, conflicts_with = "input")]
+    input_str: Option<String>,
+}
+
+impl InputOpt {
+    fn resolve(&self, day: usize) -> Result<Input, InputError> {
+        match &self.input_str {
+            Some(s) => Ok(Input::Inline(s.clone())),
+            None => runner::resolve_input(day, self.input.as_deref()),
+        }
+    }
+
+    /// Stdin and inline input can only be consumed by a single day
+    fn is_single_use(&self) -> bool {
+        self.input_str.is_some() || self.input.as_deref() == Some("-")
+    }
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Measure parsing and solving times of a day over multiple runs
+    Bench {
+        day: usize,
+        #[structopt(flatten)]
+        input: InputOpt,
+        /// Number of measured runs (excluding warm-up)
+        #[structopt(short = "n", long, default_value = "10")]
+        runs: usize,
+    },
+    /// Create source and input files of a new day and register its solver
+    New {
+        day: usize,
+    },
+    /// Describe given day: what its parts compute, its inputs and parameters
+    Help {
+        day: Option<usize>,
+    },
+    /// Step through intermediate states of days 11, 13 and 14 interactively
+    Repl {
+        day: usize,
+        #[structopt(flatten)]
+        input: InputOpt,
+    },
+    /// Serve solvers over HTTP: POST /day/{n}/part/{p} with the input as the body
+    Serve {
+        #[structopt(long, default_value = "127.0.0.1:8021")]
+        addr: String,
+    },
+    /// Download personal puzzle input of given day to data/NN/task
+    Fetch {
+        day: usize,
+        /// Puzzle site, the session token is read from AOC_SESSION or ~/.config/aoc/session
+        #[structopt(long, env = "AOC_BASE_URL", default_value = fetch::DEFAULT_BASE_URL)]
+        base_url: String,
+    },
+    /// Compute the answer of given day and part and submit it to the puzzle site
+    Submit {
+        day: usize,
+        part: Part,
+        #[structopt(flatten)]
+        input: InputOpt,
+        /// Puzzle site, the session token is read from AOC_SESSION or ~/.config/aoc/session
+        #[structopt(long, env = "AOC_BASE_URL", default_value = fetch::DEFAULT_BASE_URL)]
+        base_url: String,
+        /// Sleep until the site accepts answers again instead of failing
+        #[structopt(long)]
+        wait: bool,
+    },
+    /// Generate shell completions
+    Completions {
+        #[structopt(possible_values = &["bash", "zsh", "fish"])]
+        shell: clap::Shell,
+    },
+}
+
+fn parse_seconds(s: &str) -> Result<Duration> {
+    Ok(Duration::try_from_secs_f64(s.parse()?)?)
 }
 
 #[derive(Debug)]
@@ -42,121 +181,297 @@ impl Display for DayError {
 
 impl Error for DayError {}
 
-fn main() -> Result<()> {
-    let opt = Opt::from_args();
+/// Readable description of an error, input errors show the offending lines
+fn error_report(e: &anyhow::Error) -> String {
+    if let Some(errors) = e.downcast_ref::<ParseErrors>() {
+        return errors.report();
+    }
+    match e.downcast_ref::<ParseError>() {
+        Some(error) => error.report(),
+        None => format!("{:?}", e),
+    }
+}
 
-    match opt.day {
-        1 => {
-            let nums = utils::load_from_file(opt.input)?;
-            println!("Part 1: {}", day1::increasing_pairs_count(&nums));
-            println!("Part 2: {}", day1::windowed_increasing_count(&nums));
-        },
-        2 => {
-            let cmds = utils::load_from_file(opt.input)?;
-            let pos = day2::move_by(&cmds, |pos, cmd| pos.update1(cmd));
-            println!("Part 1:\n  position: {:?}\n  result: {}", pos, pos.result());
-            let pos = day2::move_by(&cmds, |pos, cmd| pos.update2(cmd));
-            println!("Part 2:\n  position: {:?}\n  result: {}", pos, pos.result());
-        },
-        3 => {
-            let vals = utils::load_from_file(opt.input)?;
-            println!("Part 1");
-            let (gamma, epsilon) = day3::calculate_gamma_epsilon(&vals);
-            println!("  gamma rate   = {0:3} = 0b{0:05b}", gamma);
-            println!("  epsilon rate = {0:3} = 0b{0:05b}", epsilon);
-            println!("  => power consumption = {}", gamma * epsilon);
-            println!("Part 2");
-            let oxygen = day3::calculate_rating(&vals, day3::OxygenGenerator);
-            let co2_scrubber = day3::calculate_rating(&vals, day3::CO2Scrubber);
-            println!("  oxygen generator rating = {}", oxygen);
-            println!("  CO2 scrubber rating     = {}", co2_scrubber);
-            println!("  => life support rating = {}", oxygen * co2_scrubber);
+/// Fail if some parameter is not declared by any of the given days
+fn check_params(days: &[usize], params: &[ParamValue]) -> Result<()> {
+    let mut declared: Vec<_> = days.iter()
+        .filter_map(|day| registry::get(*day))
+        .flat_map(|solver| solver.params().iter().copied())
+        .collect();
+    declared.sort_by_key(|p| p.name);
+    declared.dedup_by_key(|p| p.name);
+    Params::check(&declared, params)?;
+    Ok(())
+}
+
+fn print_records(results: &[DayResult], format: Format) {
+    let records = output::records(results);
+    match format {
+        Format::Json => output::print_json(&records),
+        Format::Csv => output::print_csv(&records),
+        Format::Text => unreachable!("Text output is not record based"),
+    }
+}
+
+fn run_single(day: usize, input: &InputOpt, config: &RunConfig, format: Format, stats: bool) -> Result<()> {
+    let result = runner::run_day(day, &input.resolve(day)?, config);
+    // a part selected with --part must be implemented, otherwise missing parts are skipped
+    let skipped = |e: &anyhow::Error| SolverError::is_unimplemented(e) && config.parts.len() > 1;
+    if format != Format::Text {
+        let unimplemented = result.run.as_ref().ok()
+            .and_then(|run| run.parts.iter().find(|p| p.answer.as_ref().is_err_and(|e| !skipped(e) && SolverError::is_unimplemented(e))))
+            .map(|p| p.part);
+        print_records(&[result], format);
+        return match unimplemented {
+            Some(part) => Err(SolverError::Unimplemented(part).into()),
+            None => Ok(()),
+        };
+    }
+
+    let run = result.run?;
+    for part in &run.parts {
+        match &part.answer {
+            Ok(answer) if answer.contains('\n') => println!("Part {}:\n{}", part.part, answer),
+            Ok(answer) => println!("Part {}: {}", part.part, answer),
+            Err(e) if skipped(e) => (),
+            Err(_) => break,
         }
-        4 => {
-            let lines: Vec<String> = utils::load_from_file(opt.input)?;
-            let (nums, mut boards) = day4::parse_lines(&lines)?;
-            println!("Winner score = {}", day4::play_to_win(&nums, &mut boards, opt.verbose).expect("Should finish"));
-            println!("Winner score = {}", day4::play_to_loose(&nums, &mut boards, opt.verbose).expect("Should finish"));
+    }
+    if stats {
+        println!();
+        output::print_phases(&[(day, &run)]);
+    }
+
+    // report the first failed part
+    match run.parts.into_iter().find(|p| p.answer.as_ref().is_err_and(|e| !skipped(e))) {
+        Some(failed) => failed.answer.map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Run multiple days, failing if any of them failed after all results are printed
+fn run_many(days: &[usize], input: Option<&str>, config: &RunConfig, format: Format, stats: bool, jobs: usize) -> Result<()> {
+    let start = Instant::now();
+    let results: Vec<_> = if jobs > 1 {
+        // collect diagnostics of each day separately so that they are not interleaved
+        let runs = pool::map(days, jobs, |day| {
+            let (diag, buffer) = Diag::buffer(config.diag.level());
+            let config = RunConfig { diag, ..config.clone() };
+            (runner::run_input(*day, input, &config), buffer)
+        });
+        runs.into_iter()
+            .map(|(result, buffer)| {
+                config.diag.append(&buffer);
+                result
+            })
+            .collect()
+    } else {
+        days.iter()
+            .map(|day| runner::run_input(*day, input, config))
+            .collect()
+    };
+    let wall = start.elapsed();
+
+    let failed = results.iter().filter(|result| result.failed()).count();
+    let status = match failed {
+        0 => Ok(()),
+        failed => Err(anyhow::anyhow!("{} of {} days failed", failed, results.len())),
+    };
+
+    match format {
+        Format::Text => output::print_table(&results),
+        format => {
+            print_records(&results, format);
+            return status;
         },
-        5 => {
-            let lines: Vec<day5::Line> = utils::load_from_file(opt.input)?;
-            let show = |vents: day5::VentsCount| {
-                if opt.verbose {
-                    println!("Hydrothermal vents:\n{}", vents);
+    }
+    if jobs > 1 {
+        let total: Duration = results.iter()
+            .filter_map(|result| result.run.as_ref().ok())
+            .map(runner::DayRun::elapsed)
+            .sum();
+        println!("Wall time: {:.2?} with {} jobs ({:.1}x)", wall, jobs, total.as_secs_f64() / wall.as_secs_f64());
+    }
+    if stats {
+        let runs: Vec<_> = results.iter()
+            .filter_map(|result| result.run.as_ref().ok().map(|run| (result.day, run)))
+            .collect();
+        println!();
+        output::print_phases(&runs);
+    }
+    status
+}
+
+fn run_check(days: &[usize], only_input: Option<&str>, config: &RunConfig) -> Result<()> {
+    let mut report = check::CheckReport::default();
+    for day in days {
+        match check::load_answers(*day)? {
+            Some(answers) => report.extend(check::check_day(*day, &answers, only_input, config)?),
+            None => println!("Day {}: no answers file", day),
+        }
+    }
+
+    for mismatch in &report.mismatches {
+        println!("{}", mismatch);
+    }
+    println!("Checked {} answers: {} passed, {} failed",
+        report.passed + report.mismatches.len(), report.passed, report.mismatches.len());
+
+    if !report.mismatches.is_empty() {
+        Err(check::CheckError::Failed(report.mismatches.len()))?;
+    }
+    if report.passed == 0 {
+        Err(check::CheckError::NoAnswers)?;
+    }
+    Ok(())
+}
+
+fn run_bench(day: usize, input: &InputOpt, runs: usize, config: &RunConfig) -> Result<()> {
+    let solver = registry::get(day).ok_or(DayError(day))?;
+    check_params(&[day], &config.params)?;
+    let input = input.resolve(day)?.read()?;
+    let results = runner::bench(solver, &input, runs, config).map_err(|e| runner::with_day(e, day))?;
+    runner::print_bench(&results);
+    Ok(())
+}
+
+fn run_submit(day: usize, part: Part, input: &InputOpt, base_url: &str, wait: bool, config: &RunConfig) -> Result<()> {
+    let client = fetch::Client::new(base_url, fetch::session_token()?);
+    let config = RunConfig { parts: vec![part], ..config.clone() };
+    let run = runner::run_day(day, &input.resolve(day)?, &config).run?;
+    let answer = match &run.part(part).expect("Selected part is run").answer {
+        Ok(answer) => answer,
+        Err(e) => anyhow::bail!("Part {} failed: {:#}", part, e),
+    };
+
+    let mut submissions = submit::Submissions::load(&submit::Submissions::path(day))?;
+    let outcome = submit::submit(&client, &mut submissions, day, part, answer, wait)?;
+    println!("Part {}: {} ({})", part, answer, outcome.verdict);
+    if !outcome.wait.is_zero() {
+        println!("Next answer can be submitted in {}s", outcome.wait.as_secs());
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = try_main() {
+        eprintln!("Error: {}", error_report(&e));
+        process::exit(1);
+    }
+}
+
+fn try_main() -> Result<()> {
+    let opt = Opt::from_args();
+    let level = Level::from_verbosity(opt.verbose);
+    let diag = match &opt.log {
+        Some(path) => Diag::file(path, level)?,
+        None => Diag::stderr(level),
+    };
+    let mut config = RunConfig::new(opt.part, diag, opt.params);
+    config.timeout = opt.timeout;
+    if opt.allocs {
+        ALLOCATOR.enable();
+    }
+    let stats = opt.stats || opt.allocs;
+    let jobs = match opt.jobs {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        jobs => jobs,
+    };
+    if opt.allocs && jobs > 1 {
+        anyhow::bail!("Allocations cannot be counted per day with multiple jobs");
+    }
+    if opt.allocs && opt.timeout.is_some() {
+        // parts that time out keep running and allocating in background
+        anyhow::bail!("Allocations cannot be counted with a timeout");
+    }
+
+    if let Some(cmd) = opt.cmd {
+        return match cmd {
+            Command::Bench { day, input, runs } => run_bench(day, &input, runs, &config),
+            Command::New { day } => {
+                for path in scaffold::new_day(day)? {
+                    println!("Wrote {}", path.display());
                 }
-                println!("Dangerous areas count = {}", vents.dangerous_area_count());
-            };
-            println!("Part 1:");
-            show(day5::VentsCount::non_diagonal(&lines));
-            println!("Part 2:");
-            show(day5::VentsCount::all(&lines));
-        },
-        6 => {
-            let fish_start = day6::load_data(&utils::load_lines(opt.input)?);
-            let fish = day6::simulate(fish_start.clone(), 80, opt.verbose);
-            println!("Part 1:");
-            println!("There are {} Lanternfish after 80 days", fish.len());
-            println!("Part 2:");
-            for days in [80, 256] {
-                let n = day6::calculate_population(&fish_start, days, opt.verbose);
-                println!("There are {} Lanternfish after {} days", n, days);
-            }
+                Ok(())
+            },
+            Command::Help { day: Some(day) } => help::print_day(day),
+            Command::Help { day: None } => {
+                Opt::clap().print_long_help()?;
+                println!();
+                Ok(())
+            },
+            Command::Repl { day, input } => repl::run(day, &input.resolve(day)?.read()?),
+            Command::Serve { addr } => {
+                let server = server::Server::bind(&addr, config)?;
+                println!("Listening on http://{}", server.local_addr()?);
+                Ok(server.serve()?)
+            },
+            Command::Fetch { day, base_url } => {
+                let client = fetch::Client::new(&base_url, fetch::session_token()?);
+                let path = fetch::fetch(&client, day)?;
+                println!("Wrote {}", path.display());
+                Ok(())
+            },
+            Command::Submit { day, part, input, base_url, wait } => run_submit(day, part, &input, &base_url, wait, &config),
+            Command::Completions { shell } => help::print_completions(Opt::clap(), shell),
+        };
+    }
+    let selection = match opt.days {
+        Some(selection) => selection,
+        None => clap::Error::with_description(
+            "Day number or a subcommand is required", clap::ErrorKind::MissingRequiredArgument).exit(),
+    };
 
-        },
-        7 => {
-            let positions = day7::load_data(&utils::load_lines(opt.input)?);
-            println!("Part 1:\n{:?}", day7::best_position(&positions, day7::FuelCost::Constant));
-            println!("Part 2:\n{:?}", day7::best_position(&positions, day7::FuelCost::Increasing));
-        },
-        8 => {
-            let lines = day8::load_data(&utils::load_lines(opt.input)?);
-            if opt.verbose {
-                day8::print_lines(&lines);
-            }
-            println!("Part 1: {}", day8::solve_part1(&lines));
-        },
-        9 => {
-            let heights = day9::load_data(&utils::load_lines(opt.input)?);
-            println!("Part 1: {}", day9::part_1(&heights, opt.verbose));
-            println!("Part 2: {}", day9::part_2(&heights, opt.verbose));
-        },
-        10 => {
-            let lines = &utils::load_lines(opt.input)?;
-            println!("Part 1: {}", day10::part_1(&lines, opt.verbose));
-            println!("Part 2: {}", day10::part_2(&lines, opt.verbose));
-        },
-        11 => {
-            let lines = utils::load_lines(opt.input)?;
-            println!("Part 1: {}", day11::OctopusGrid::new(&lines).part_1(100, opt.verbose));
-            println!("Part 2: {}", day11::OctopusGrid::new(&lines).part_2(opt.verbose));
-        },
-        12 => {
-            let graph = day12::Graph::new(&utils::load_lines(opt.input)?);
-            println!("Part 1: {}", graph.part_1(opt.verbose));
-            println!("Part 2: {}", graph.part_2(opt.verbose));
-        },
-        13 => {
-            println!("Part 1: {}", day13::part_1(File::open(opt.input.clone())?));
-            println!("Part 2: {}", day13::part_2(File::open(opt.input)?));
-        },
-        14 => {
-            println!("Part 1: {}", day14::part_1(File::open(opt.input.clone())?, opt.verbose));
-            println!("Part 2: {}", day14::part_2(File::open(opt.input)?, opt.verbose));
-        },
-        15 => {
-            println!("Part 1: {}", day15::part_1(File::open(opt.input.clone())?, opt.verbose));
-            println!("Part 2: {}", day15::part_2(File::open(opt.input)?, opt.verbose));
-        },
-        16 => {
-            println!("Part 1: {}", day16::part_1(File::open(opt.input.clone())?, opt.verbose));
-            println!("Part 2: {}", day16::part_2(File::open(opt.input)?, opt.verbose));
-        },
-        17 => {
-            println!("Part 1: {}", day17::part_1(File::open(opt.input.clone())?, opt.verbose));
-            println!("Part 2: {}", day17::part_2(File::open(opt.input)?, opt.verbose));
-        },
-        day => Err(io::Error::new(io::ErrorKind::InvalidData, DayError(day)))?,
+    if let Selection::Single(day) = selection {
+        registry::get(day).ok_or(DayError(day))?;
+    }
+    if selection.days().is_empty() {
+        anyhow::bail!("No implemented days match {}, available: {}..={}", selection,
+            registry::days().min().unwrap_or_default(), registry::days().max().unwrap_or_default());
+    }
+    check_params(&selection.days(), &config.params)?;
+
+    if opt.watch {
+        let day = match selection {
+            Selection::Single(day) => day,
+            _ => anyhow::bail!("Only a single day can be watched"),
+        };
+        let path = match opt.input.resolve(day)? {
+            Input::File(path) => path,
+            input => anyhow::bail!("Only input files can be watched, not {}", input),
+        };
+        watch::watch(&path, || run_single(day, &opt.input, &config, opt.format, stats));
+    }
+
+    if opt.check {
+        if opt.input.is_single_use() {
+            anyhow::bail!("Answers can only be checked for inputs from the data directory");
+        }
+        // only check answers for the given input file if any
+        let only_input = opt.input.input.as_deref()
+            .map(|input| check::input_name(&selection.days(), input))
+            .transpose()?;
+        return run_check(&selection.days(), only_input.as_deref(), &config);
+    }
+
+    match selection {
+        Selection::Single(day) => run_single(day, &opt.input, &config, opt.format, stats)?,
+        _ if opt.input.is_single_use() => anyhow::bail!("Stdin or inline input can only be used for a single day"),
+        selection => run_many(&selection.days(), opt.input.input.as_deref(), &config, opt.format, stats, jobs)?,
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_params() {
+        let params = |values: &[&str]| values.iter().map(|v| v.parse().unwrap()).collect::<Vec<_>>();
+        assert!(check_params(&[6, 17], &params(&["days_1=10", "max_y=50"])).is_ok());
+        let e = check_params(&[6], &params(&["max_y=50"])).unwrap_err();
+        assert_eq!(e.to_string(), "Unknown parameter 'max_y', available: days_1, days_2");
+    }
+}
@@ -0,0 +1,251 @@
+use std::{str::FromStr, time::Duration};
+
+use thiserror::Error;
+
+use aoc::{solver::{Part, SolverError}, alloc::AllocStats};
+
+use crate::runner::{DayResult, DayRun, RunError};
+
+/// Format of printed results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown format: {0} (expected text, json or csv)")]
+pub struct FormatError(String);
+
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            other => Err(FormatError(other.to_string())),
+        }
+    }
+}
+
+/// Result of a single part, the uniform unit of machine-readable output
+pub struct Record {
+    pub day: usize,
+    pub part: Part,
+    pub answer: Result<String, String>,
+    pub elapsed: Duration,
+    pub allocs: Option<AllocStats>,
+    pub input: String,
+}
+
+/// Flatten day results into records, skipping parts that are not implemented
+pub fn records(results: &[DayResult]) -> Vec<Record> {
+    let mut records = Vec::new();
+    for result in results {
+        let record = |part, answer, elapsed, allocs| Record {
+            day: result.day,
+            part,
+            answer,
+            elapsed,
+            allocs,
+            input: result.input.clone(),
+        };
+        match &result.run {
+            Ok(run) => {
+                for part in &run.parts {
+                    let answer = match &part.answer {
+                        Ok(answer) => Ok(answer.clone()),
+                        Err(e) if SolverError::is_unimplemented(e) => continue,
+                        Err(e) => Err(e.to_string()),
+                    };
+                    records.push(record(part.part, answer, part.elapsed, part.allocs));
+                }
+            },
+            Err(e) => {
+                for part in Part::ALL {
+                    records.push(record(part, Err(e.to_string()), Duration::ZERO, None));
+                }
+            },
+        }
+    }
+    records
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn print_json(records: &[Record]) {
+    println!("[");
+    for (i, r) in records.iter().enumerate() {
+        let (answer, error) = match &r.answer {
+            Ok(answer) => (json_string(answer), "null".to_string()),
+            Err(e) => ("null".to_string(), json_string(e)),
+        };
+        let allocs = match r.allocs {
+            Some(a) => format!("\"allocations\": {}, \"bytes\": {}, \"peak_bytes\": {}", a.allocations, a.bytes, a.peak),
+            None => "\"allocations\": null, \"bytes\": null, \"peak_bytes\": null".to_string(),
+        };
+        let sep = if i + 1 < records.len() { "," } else { "" };
+        println!("  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"error\": {}, \"elapsed_s\": {:.9}, {}, \"input\": {}}}{}",
+            r.day, r.part, answer, error, r.elapsed.as_secs_f64(), allocs, json_string(&r.input), sep);
+    }
+    println!("]");
+}
+
+pub fn print_csv(records: &[Record]) {
+    println!("day,part,answer,error,elapsed_s,allocations,bytes,peak_bytes,input");
+    for r in records {
+        let (answer, error) = match &r.answer {
+            Ok(answer) => (csv_field(answer), String::new()),
+            Err(e) => (String::new(), csv_field(e)),
+        };
+        let allocs = match r.allocs {
+            Some(a) => format!("{},{},{}", a.allocations, a.bytes, a.peak),
+            None => ",,".to_string(),
+        };
+        println!("{},{},{},{},{:.9},{},{}", r.day, r.part, answer, error, r.elapsed.as_secs_f64(), allocs, csv_field(&r.input));
+    }
+}
+
+fn answer_cell(run: &DayRun, part: Part) -> String {
+    match run.part(part).map(|p| &p.answer) {
+        // multi-line answers would break the table
+        Some(Ok(answer)) if answer.contains('\n') => format!("<{} lines>", answer.lines().count()),
+        Some(Ok(answer)) => answer.clone(),
+        Some(Err(e)) if SolverError::is_unimplemented(e) => "-".to_string(),
+        Some(Err(e)) if RunError::is_timeout(e) => "TIMEOUT".to_string(),
+        Some(Err(e)) => format!("error: {}", e),
+        None => "-".to_string(),
+    }
+}
+
+/// Print a summary table of multiple days
+pub fn print_table(results: &[DayResult]) {
+    let header = ["Day", "Part 1", "Part 2", "Time"].map(str::to_string);
+    let rows: Vec<[String; 4]> = results.iter()
+        .map(|result| match &result.run {
+            Ok(run) => [
+                result.day.to_string(),
+                answer_cell(run, Part::One),
+                answer_cell(run, Part::Two),
+                format!("{:.2?}", run.elapsed()),
+            ],
+            Err(e) => [result.day.to_string(), format!("error: {}", e), String::new(), String::new()],
+        })
+        .collect();
+
+    let mut widths = header.each_ref().map(|h| h.len());
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+
+    let print_row = |row: &[String; 4]| {
+        println!("{:>w0$} | {:<w1$} | {:<w2$} | {:>w3$}", row[0], row[1], row[2], row[3],
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]);
+    };
+    print_row(&header);
+    println!("{}", widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("-+-"));
+    for row in &rows {
+        print_row(row);
+    }
+
+    let total: Duration = results.iter()
+        .filter_map(|result| result.run.as_ref().ok())
+        .map(DayRun::elapsed)
+        .sum();
+    println!("Total: {:.2?}", total);
+}
+
+/// Human readable number of bytes
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Print time and allocations of parsing and each part of given days
+pub fn print_phases(runs: &[(usize, &DayRun)]) {
+    let with_allocs = runs.iter().any(|(_, run)| run.parse_allocs.is_some());
+    if with_allocs {
+        println!("{:>3} | {:<6} | {:>10} | {:>10} | {:>10} | {:>10}", "Day", "Phase", "Time", "Allocs", "Bytes", "Peak");
+        println!("{:-<3}-+-{:-<6}-+-{:->10}-+-{:->10}-+-{:->10}-+-{:->10}", "", "", "", "", "", "");
+    } else {
+        println!("{:>3} | {:<6} | {:>10}", "Day", "Phase", "Time");
+        println!("{:-<3}-+-{:-<6}-+-{:->10}", "", "", "");
+    }
+    for (day, run) in runs {
+        let parse = ("parse".to_string(), run.parse, run.parse_allocs);
+        let parts = run.parts.iter()
+            .filter(|p| !p.answer.as_ref().is_err_and(SolverError::is_unimplemented))
+            .map(|p| (format!("part {}", p.part), p.elapsed, p.allocs));
+        for (phase, elapsed, allocs) in std::iter::once(parse).chain(parts) {
+            let time = format!("{:.2?}", elapsed);
+            match allocs {
+                Some(a) => println!("{:>3} | {:<6} | {:>10} | {:>10} | {:>10} | {:>10}", day, phase, time,
+                    a.allocations, format_bytes(a.bytes), format_bytes(a.peak)),
+                None => println!("{:>3} | {:<6} | {:>10}", day, phase, time),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(json_string("a\"b\\c\nd"), r#""a\"b\\c\nd""#);
+        assert_eq!(json_string("\x01"), r#""\u0001""#);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format_bytes(1000), "1000 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("123"), "123");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
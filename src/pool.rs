@@ -0,0 +1,58 @@
+use std::{sync::{atomic::{AtomicUsize, Ordering}, mpsc}, thread};
+
+/// Apply `f` to all items on `jobs` worker threads
+///
+/// Idle workers take items one at a time, so a slow item does not hold up the rest.
+/// Results are returned in the order of items regardless of completion order.
+pub fn map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            let (tx, next, f) = (tx.clone(), &next, &f);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                match items.get(index) {
+                    Some(item) => tx.send((index, f(item))).expect("Results are received until all workers finish"),
+                    None => break,
+                }
+            });
+        }
+    });
+    drop(tx);
+
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    for (index, result) in rx {
+        results[index] = Some(result);
+    }
+    results.into_iter()
+        .map(|r| r.expect("Each item has a result"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_order() {
+        let items: Vec<u64> = (0..20).collect();
+        // earlier items finish later
+        let results = map(&items, 4, |i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * 2
+        });
+        assert_eq!(results, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn no_items() {
+        assert!(map(&[] as &[u8], 4, |i| *i).is_empty());
+    }
+}
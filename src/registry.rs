@@ -0,0 +1,103 @@
+use std::{str::FromStr, num::ParseIntError, ops::RangeInclusive, fmt::Display};
+
+use thiserror::Error;
+
+use aoc::solver::DynSolver;
+
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12, day13, day14, day15, day16, day17};
+
+pub type SolverRef = &'static (dyn DynSolver + Sync);
+
+/// All implemented days with their solvers, in order
+const SOLVERS: &[(usize, SolverRef)] = &[
+    (1, &day1::Day1),
+    (2, &day2::Day2),
+    (3, &day3::Day3),
+    (4, &day4::Day4),
+    (5, &day5::Day5),
+    (6, &day6::Day6),
+    (7, &day7::Day7),
+    (8, &day8::Day8),
+    (9, &day9::Day9),
+    (10, &day10::Day10),
+    (11, &day11::Day11),
+    (12, &day12::Day12),
+    (13, &day13::Day13),
+    (14, &day14::Day14),
+    (15, &day15::Day15),
+    (16, &day16::Day16),
+    (17, &day17::Day17),
+];
+
+/// Get solver for given day
+pub fn get(day: usize) -> Option<SolverRef> {
+    SOLVERS.iter()
+        .find(|(d, _)| *d == day)
+        .map(|(_, solver)| *solver)
+}
+
+/// Iterate over numbers of all implemented days
+pub fn days() -> impl Iterator<Item = usize> {
+    SOLVERS.iter().map(|(day, _)| *day)
+}
+
+/// Days selected on the command line
+#[derive(Debug, Clone)]
+pub enum Selection {
+    Single(usize),
+    Range(RangeInclusive<usize>),
+    All,
+}
+
+impl Selection {
+    /// Implemented days that match this selection
+    pub fn days(&self) -> Vec<usize> {
+        days()
+            .filter(|day| match self {
+                Selection::Single(d) => d == day,
+                Selection::Range(range) => range.contains(day),
+                Selection::All => true,
+            })
+            .collect()
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selection::Single(day) => write!(f, "{}", day),
+            Selection::Range(range) => write!(f, "{}..={}", range.start(), range.end()),
+            Selection::All => write!(f, "all"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SelectionError {
+    #[error("Invalid day number: {0}")]
+    InvalidNumber(#[from] ParseIntError),
+    #[error("Empty range of days: {0}")]
+    EmptyRange(String),
+}
+
+impl FromStr for Selection {
+    type Err = SelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Selection::All);
+        }
+        let range = if let Some((start, end)) = s.split_once("..=") {
+            start.parse()? ..= end.parse()?
+        } else if let Some((start, end)) = s.split_once("..") {
+            let end: usize = end.parse()?;
+            start.parse()? ..= end.checked_sub(1).ok_or_else(|| SelectionError::EmptyRange(s.to_string()))?
+        } else {
+            return Ok(Selection::Single(s.parse()?));
+        };
+        if range.is_empty() {
+            return Err(SelectionError::EmptyRange(s.to_string()));
+        }
+        Ok(Selection::Range(range))
+    }
+}
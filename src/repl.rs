@@ -0,0 +1,251 @@
+use std::{io::{self, BufRead, Write}, collections::{HashSet, BTreeMap}, panic::{self, AssertUnwindSafe}};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use aoc::{diag::Diag, grid::INVERSE, solver::Parsed};
+
+use crate::{registry, runner, day11::OctopusGrid, day13::Dots, day14::Polymerisator};
+
+/// Intermediate state of a day that can be advanced one step at a time
+trait State: Clone {
+    /// Advance by one step, `false` if the state cannot change anymore
+    fn step(&mut self) -> Result<bool>;
+    fn show(&self) -> String;
+    fn count(&self) -> String;
+}
+
+impl State for Dots {
+    fn step(&mut self) -> Result<bool> {
+        match self.folded() {
+            Some(folded) => {
+                *self = folded;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn count(&self) -> String {
+        format!("{} dots", Dots::count(self))
+    }
+}
+
+impl State for Polymerisator {
+    fn step(&mut self) -> Result<bool> {
+        self.grow()?;
+        Ok(true)
+    }
+
+    fn show(&self) -> String {
+        self.polymer().to_string()
+    }
+
+    fn count(&self) -> String {
+        let counts: BTreeMap<_, _> = self.counts().into_iter().collect();
+        let counts: Vec<_> = counts.iter()
+            .map(|(c, n)| format!("{}={}", c, n))
+            .collect();
+        format!("length {}: {}", self.polymer().len(), counts.join(" "))
+    }
+}
+
+/// Octopuses together with the ones that flashed in the last step
+#[derive(Clone)]
+struct Octopuses {
+    grid: OctopusGrid,
+    flashed: HashSet<(usize, usize)>,
+    flashes: usize,
+}
+
+impl State for Octopuses {
+    fn step(&mut self) -> Result<bool> {
+        self.flashed = self.grid.step(&Diag::default());
+        self.flashes += self.flashed.len();
+        Ok(true)
+    }
+
+    fn show(&self) -> String {
+        self.grid.to_string(INVERSE, |pos| self.flashed.contains(&pos), |val| val.to_string())
+    }
+
+    fn count(&self) -> String {
+        format!("{} flashed in the last step, {} in total", self.flashed.len(), self.flashes)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReplError {
+    #[error("No interactive mode for day {0}, available for days 11, 13 and 14")]
+    Unsupported(usize),
+}
+
+const HELP: &str = "\
+Commands:
+  step [n]  advance by n steps (default 1)
+  show      print current state
+  count     print counts of current state
+  reset     go back to the parsed input
+  help      print this message
+  quit      exit (or Ctrl-D)";
+
+/// Current and initial state with the number of steps done
+struct Session<S> {
+    initial: S,
+    current: S,
+    steps: usize,
+}
+
+impl<S: State> Session<S> {
+    fn new(initial: S) -> Self {
+        Self { current: initial.clone(), initial, steps: 0 }
+    }
+
+    /// Execute a single command, returns what to print or `None` to quit
+    fn execute(&mut self, line: &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let output = match (words.next(), words.next()) {
+            (None, _) => String::new(),
+            (Some("step"), n) => match n.map_or(Ok(1), str::parse::<usize>) {
+                Ok(n) => {
+                    let mut done = 0;
+                    let mut stopped = None;
+                    while done < n && stopped.is_none() {
+                        match self.current.step() {
+                            // count each step right away, a panic in the next one must not lose it
+                            Ok(true) => {
+                                done += 1;
+                                self.steps += 1;
+                            },
+                            Ok(false) => stopped = Some("nothing more to do".to_string()),
+                            Err(e) => stopped = Some(e.to_string()),
+                        }
+                    }
+                    match stopped {
+                        Some(reason) => format!("Stopped after {} steps, {} (step {})", done, reason, self.steps),
+                        None => format!("Step {}", self.steps),
+                    }
+                },
+                Err(e) => format!("Invalid number of steps: {}", e),
+            },
+            (Some("show"), _) => self.current.show(),
+            (Some("count"), _) => self.current.count(),
+            (Some("reset"), _) => {
+                self.current = self.initial.clone();
+                self.steps = 0;
+                "Step 0".to_string()
+            },
+            (Some("help"), _) => HELP.to_string(),
+            (Some("quit" | "exit"), _) => return None,
+            (Some(other), _) => format!("Unknown command: {}, try help", other),
+        };
+        Some(output)
+    }
+
+    fn run(&mut self, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+        write!(out, "> ")?;
+        out.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            // a panicking solver should not end the session
+            let output = panic::catch_unwind(AssertUnwindSafe(|| self.execute(&line)))
+                .unwrap_or_else(|payload| Some(format!("Error: {}", runner::panic_message(payload.as_ref()))));
+            match output {
+                Some(output) if output.is_empty() => (),
+                Some(output) => writeln!(out, "{}", output)?,
+                None => return Ok(()),
+            }
+            write!(out, "> ")?;
+            out.flush()?;
+        }
+        writeln!(out)
+    }
+}
+
+fn downcast<T: Clone + 'static>(parsed: &Parsed) -> T {
+    parsed.downcast_ref::<T>()
+        .expect("Solver parsed unexpected type")
+        .clone()
+}
+
+/// Interactively step through the state of given day, reading commands from stdin
+pub fn run(day: usize, input: &str) -> Result<()> {
+    let solver = registry::get(day).ok_or(crate::DayError(day))?;
+    if !matches!(day, 11 | 13 | 14) {
+        Err(ReplError::Unsupported(day))?;
+    }
+    let parsed = solver.load(input).map_err(|e| runner::with_day(e, day))?;
+
+    let stdin = io::stdin();
+    let (input, out) = (stdin.lock(), io::stdout());
+    println!("{}", HELP);
+    match day {
+        11 => Session::new(Octopuses { grid: downcast(&parsed), flashed: HashSet::new(), flashes: 0 }).run(input, out)?,
+        13 => Session::new(downcast::<Dots>(&parsed)).run(input, out)?,
+        14 => Session::new(downcast::<Polymerisator>(&parsed)).run(input, out)?,
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_dots() {
+        let dots = Dots::new("6,10\n6,4\n9,10\n\nfold along y=7\n").unwrap();
+        let mut session = Session::new(dots);
+        let commands = "count\nstep 2\ncount\nreset\ncount\nquit\nstep\n";
+        let mut out = Vec::new();
+        session.run(commands.as_bytes(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+> 3 dots
+> Stopped after 1 steps, nothing more to do (step 1)
+> 2 dots
+> Step 0
+> 3 dots
+> ");
+    }
+
+    #[test]
+    fn panicking_step() {
+        /// Counter that panics when stepped past its limit
+        #[derive(Clone)]
+        struct Limited(usize);
+
+        impl State for Limited {
+            fn step(&mut self) -> Result<bool> {
+                assert!(self.0 < 3, "Limit reached");
+                self.0 += 1;
+                Ok(true)
+            }
+
+            fn show(&self) -> String {
+                self.0.to_string()
+            }
+
+            fn count(&self) -> String {
+                self.0.to_string()
+            }
+        }
+
+        let mut session = Session::new(Limited(0));
+        let mut out = Vec::new();
+        session.run("step 5\ncount\nstep\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "> Error: Limit reached\n> 3\n> Error: Limit reached\n> \n");
+        assert_eq!(session.steps, 3);
+    }
+
+    #[test]
+    fn fold_at_dot() {
+        let e = Dots::new("0,1\n\nfold along y=1\n").unwrap_err();
+        assert_eq!((e.reason.as_str(), e.line), ("Fold line goes through a dot", Some(3)));
+        let e = Dots::new("0,20\n\nfold along x=5\nfold along y=2\n").unwrap_err();
+        assert_eq!((e.reason.as_str(), e.line), ("Fold would move a dot past the edge", Some(4)));
+    }
+}
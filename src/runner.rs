@@ -0,0 +1,381 @@
+use std::{path::PathBuf, time::{Duration, Instant}, fs, io::{self, Read}, fmt::Display, sync::{Arc, mpsc::{self, RecvTimeoutError}}, thread, panic::{self, AssertUnwindSafe}, any::Any};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use aoc::{alloc::AllocStats, diag::Diag, error::{ParseError, ParseErrors}, solver::{Answer, Part, Parsed, SolverError, Context, Params, ParamValue}, bench::{self, Stats}};
+
+use crate::registry::{self, SolverRef};
+
+/// Directory with inputs, laid out as `data/NN/<name>`
+pub const DATA_DIR: &str = "data";
+
+/// Path to a file in the data directory of given day
+pub fn input_path(day: usize, name: &str) -> PathBuf {
+    PathBuf::from(DATA_DIR).join(format!("{:02}", day)).join(name)
+}
+
+/// Name of the puzzle input, used when no input is given
+pub const DEFAULT_INPUT: &str = "task";
+
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("No input '{name}' for day {day}, available: {}", .available.join(", "))]
+    NotFound {
+        day: usize,
+        name: String,
+        available: Vec<String>,
+    },
+}
+
+/// Names of input files in the data directory of given day
+pub fn available_inputs(day: usize) -> Vec<String> {
+    let dir = PathBuf::from(DATA_DIR).join(format!("{:02}", day));
+    let mut names: Vec<_> = fs::read_dir(dir).into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name != "answers" && name != "submissions")
+        .collect();
+    names.sort();
+    names
+}
+
+/// Where to read puzzle input from
+#[derive(Debug, Clone)]
+pub enum Input {
+    File(PathBuf),
+    Stdin,
+    Inline(String),
+}
+
+impl Input {
+    /// Read the whole input
+    pub fn read(&self) -> io::Result<String> {
+        match self {
+            Input::File(path) => fs::read_to_string(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e))),
+            Input::Stdin => {
+                let mut s = String::new();
+                io::stdin().read_to_string(&mut s)?;
+                Ok(s)
+            },
+            Input::Inline(s) => Ok(s.clone()),
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::File(path) => write!(f, "{}", path.display()),
+            Input::Stdin => write!(f, "<stdin>"),
+            Input::Inline(_) => write!(f, "<inline>"),
+        }
+    }
+}
+
+/// Resolve input given on the command line
+///
+/// The input is a name of a file in the data directory of given day (e.g. `example2`),
+/// a path to any existing file or `-` for stdin. Defaults to the puzzle input.
+pub fn resolve_input(day: usize, input: Option<&str>) -> Result<Input, InputError> {
+    let name = input.unwrap_or(DEFAULT_INPUT);
+    if name == "-" {
+        return Ok(Input::Stdin);
+    }
+    let path = input_path(day, name);
+    if path.is_file() {
+        return Ok(Input::File(path));
+    }
+    let path = PathBuf::from(name);
+    if path.is_file() {
+        return Ok(Input::File(path));
+    }
+    Err(InputError::NotFound { day, name: name.to_string(), available: available_inputs(day) })
+}
+
+/// What to run for each day
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    pub parts: Vec<Part>,
+    pub diag: Diag,
+    pub params: Vec<ParamValue>,
+    /// Give up on a part after this time, parts are run on worker threads then
+    pub timeout: Option<Duration>,
+}
+
+impl RunConfig {
+    /// Run only given part or both if `None`
+    pub fn new(part: Option<Part>, diag: Diag, params: Vec<ParamValue>) -> Self {
+        let parts = match part {
+            Some(part) => vec![part],
+            None => Part::ALL.to_vec(),
+        };
+        Self { parts, diag, params, timeout: None }
+    }
+
+    /// Context for given solver, with values of the parameters it declares
+    pub fn context(&self, solver: SolverRef) -> Context {
+        Context {
+            diag: self.diag.clone(),
+            params: Params::new(solver.params(), &self.params),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error("TIMEOUT after {0:.2?}")]
+    Timeout(Duration),
+    #[error("Solver panicked: {0}")]
+    Panicked(String),
+}
+
+impl RunError {
+    pub fn is_timeout(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<RunError>(), Some(RunError::Timeout(_)))
+    }
+
+    pub fn is_panic(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<RunError>(), Some(RunError::Panicked(_)))
+    }
+}
+
+/// Message given to `panic!`
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown reason".to_string())
+}
+
+/// Call `f`, turning its panic into `RunError::Panicked` so that other days still run
+fn guarded<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(RunError::Panicked(panic_message(payload.as_ref())).into()))
+}
+
+pub struct PartRun {
+    pub part: Part,
+    pub answer: Result<Answer>,
+    pub elapsed: Duration,
+    /// Only measured with counting of allocations enabled
+    pub allocs: Option<AllocStats>,
+}
+
+pub struct DayRun {
+    pub parse: Duration,
+    pub parse_allocs: Option<AllocStats>,
+    pub parts: Vec<PartRun>,
+}
+
+impl DayRun {
+    /// Total time including parsing
+    pub fn elapsed(&self) -> Duration {
+        self.parse + self.parts.iter().map(|p| p.elapsed).sum::<Duration>()
+    }
+
+    pub fn part(&self, part: Part) -> Option<&PartRun> {
+        self.parts.iter().find(|p| p.part == part)
+    }
+}
+
+/// Call `f` measuring its time and allocations
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration, Option<AllocStats>) {
+    let start = Instant::now();
+    let (result, allocs) = crate::ALLOCATOR.measure(f);
+    (result, start.elapsed(), allocs)
+}
+
+/// Solve a part on a worker thread, waiting for it at most `timeout`
+///
+/// A thread that does not finish in time cannot be stopped, so it is left running in background.
+fn solve_with_timeout(solver: SolverRef, parsed: &Arc<Parsed>, part: Part, ctx: &Context, timeout: Duration) -> PartRun {
+    let (tx, rx) = mpsc::channel();
+    let (parsed, ctx) = (Arc::clone(parsed), ctx.clone());
+    thread::spawn(move || {
+        let _ = tx.send(measure(|| guarded(|| solver.solve(&parsed, part, &ctx))));
+    });
+
+    let start = Instant::now();
+    match rx.recv_timeout(timeout) {
+        Ok((answer, elapsed, allocs)) => PartRun { part, answer, elapsed, allocs },
+        Err(RecvTimeoutError::Timeout) => PartRun {
+            part,
+            answer: Err(RunError::Timeout(timeout).into()),
+            elapsed: timeout,
+            allocs: None,
+        },
+        Err(RecvTimeoutError::Disconnected) => PartRun {
+            part,
+            answer: Err(RunError::Panicked("worker thread exited".to_string()).into()),
+            elapsed: start.elapsed(),
+            allocs: None,
+        },
+    }
+}
+
+/// Parse the input once and solve the selected parts, measuring each step
+pub fn run(solver: SolverRef, input: &str, config: &RunConfig) -> Result<DayRun> {
+    let (parsed, parse, parse_allocs) = measure(|| guarded(|| solver.load(input)));
+    let parsed = Arc::new(parsed?);
+
+    let ctx = config.context(solver);
+    let parts = config.parts.iter()
+        .map(|part| match config.timeout {
+            Some(timeout) => solve_with_timeout(solver, &parsed, *part, &ctx, timeout),
+            None => {
+                let (answer, elapsed, allocs) = measure(|| guarded(|| solver.solve(&parsed, *part, &ctx)));
+                PartRun { part: *part, answer, elapsed, allocs }
+            },
+        })
+        .collect();
+
+    Ok(DayRun { parse, parse_allocs, parts })
+}
+
+/// Results of running a day on given input
+pub struct DayResult {
+    pub day: usize,
+    pub input: String,
+    pub run: Result<DayRun>,
+}
+
+impl DayResult {
+    /// Check if the day or any of its implemented parts failed
+    pub fn failed(&self) -> bool {
+        match &self.run {
+            Ok(run) => run.parts.iter().any(|p| p.answer.as_ref().is_err_and(|e| !SolverError::is_unimplemented(e))),
+            Err(_) => true,
+        }
+    }
+}
+
+/// Run given day reading its input
+pub fn run_day(day: usize, input: &Input, config: &RunConfig) -> DayResult {
+    let run = registry::get(day)
+        .ok_or_else(|| crate::DayError(day).into())
+        .and_then(|solver| run(solver, &input.read()?, config).map_err(|e| with_day(e, day)));
+    DayResult { day, input: input.to_string(), run }
+}
+
+/// Fill in the day of input errors reported by a loader
+pub fn with_day(mut e: anyhow::Error, day: usize) -> anyhow::Error {
+    if let Some(error) = e.downcast_mut::<ParseError>() {
+        error.day.get_or_insert(day);
+    }
+    if let Some(errors) = e.downcast_mut::<ParseErrors>() {
+        for error in &mut errors.0 {
+            error.day.get_or_insert(day);
+        }
+    }
+    e
+}
+
+/// Run given day on an input resolved with `resolve_input`
+pub fn run_input(day: usize, input: Option<&str>, config: &RunConfig) -> DayResult {
+    match resolve_input(day, input) {
+        Ok(input) => run_day(day, &input, config),
+        Err(e) => DayResult {
+            day,
+            input: input.unwrap_or(DEFAULT_INPUT).to_string(),
+            run: Err(e.into()),
+        },
+    }
+}
+
+/// Measure parsing and each selected part `runs` times, excluding a warm-up run
+pub fn bench(solver: SolverRef, input: &str, runs: usize, config: &RunConfig) -> Result<Vec<(String, Stats)>> {
+    let mut results = Vec::new();
+
+    let (parsed, samples) = bench::measure(runs, || guarded(|| solver.load(input)))?;
+    results.extend(Stats::from_samples(&samples).map(|s| ("parse".to_string(), s)));
+
+    let ctx = config.context(solver);
+    for part in config.parts.iter().copied() {
+        // the warm-up tells whether the part is implemented
+        let samples = match bench::measure(runs, || guarded(|| solver.solve(&parsed, part, &ctx))) {
+            Ok((_, samples)) => samples,
+            Err(e) if SolverError::is_unimplemented(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        results.extend(Stats::from_samples(&samples).map(|s| (format!("part {}", part), s)));
+    }
+
+    Ok(results)
+}
+
+/// Print benchmark statistics as a table
+pub fn print_bench(results: &[(String, Stats)]) {
+    println!("{:<6} | {:>5} | {:>10} | {:>10} | {:>10} | {:>10}", "Step", "Runs", "Min", "Median", "Mean", "Stddev");
+    println!("{:-<6}-+-{:->5}-+-{:->10}-+-{:->10}-+-{:->10}-+-{:->10}", "", "", "", "", "", "");
+    for (step, stats) in results {
+        println!("{:<6} | {:>5} | {:>10} | {:>10} | {:>10} | {:>10}", step, stats.runs,
+            format!("{:.2?}", stats.min),
+            format!("{:.2?}", stats.median),
+            format!("{:.2?}", stats.mean),
+            format!("{:.2?}", stats.stddev));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::solver::{Solver, DynSolver, Description};
+
+    /// Part 1 panics, part 2 sleeps for the number of milliseconds given as input
+    struct Stub;
+
+    impl Solver for Stub {
+        type Input = u64;
+
+        const DESCRIPTION: Description = Description { title: "Stub", part_1: "", part_2: "" };
+
+        fn parse(&self, input: &str) -> Result<Self::Input> {
+            Ok(input.parse()?)
+        }
+
+        fn part_1(&self, _millis: &Self::Input, _ctx: &Context) -> Result<Answer> {
+            panic!("Stub failed")
+        }
+
+        fn part_2(&self, millis: &Self::Input, _ctx: &Context) -> Result<Answer> {
+            thread::sleep(Duration::from_millis(*millis));
+            Ok(millis.to_string())
+        }
+    }
+
+    #[test]
+    fn panics() {
+        let run = run(&Stub, "0", &RunConfig::new(None, Diag::default(), vec![])).unwrap();
+        let e = run.part(Part::One).unwrap().answer.as_ref().unwrap_err();
+        assert!(RunError::is_panic(e));
+        assert_eq!(e.to_string(), "Solver panicked: Stub failed");
+        assert_eq!(run.part(Part::Two).unwrap().answer.as_ref().unwrap(), "0");
+    }
+
+    #[test]
+    fn bench_panics() {
+        let config = RunConfig::new(None, Diag::default(), vec![]);
+        let results = bench(&Stub, "0", 2, &RunConfig { parts: vec![Part::Two], ..config.clone() }).unwrap();
+        let steps: Vec<_> = results.iter().map(|(step, stats)| (step.as_str(), stats.runs)).collect();
+        assert_eq!(steps, [("parse", 2), ("part 2", 2)]);
+        assert!(RunError::is_panic(&bench(&Stub, "0", 2, &config).unwrap_err()));
+    }
+
+    #[test]
+    fn timeouts() {
+        let parsed: Arc<Parsed> = Arc::new(Stub.load("1000").unwrap());
+        let timeout = Duration::from_millis(50);
+        let run = solve_with_timeout(&Stub, &parsed, Part::Two, &Context::default(), timeout);
+        assert!(RunError::is_timeout(run.answer.as_ref().unwrap_err()));
+        assert_eq!(run.elapsed, timeout);
+
+        let parsed: Arc<Parsed> = Arc::new(Stub.load("0").unwrap());
+        let run = solve_with_timeout(&Stub, &parsed, Part::Two, &Context::default(), timeout);
+        assert_eq!(run.answer.unwrap(), "0");
+        let run = solve_with_timeout(&Stub, &parsed, Part::One, &Context::default(), timeout);
+        assert!(RunError::is_panic(run.answer.as_ref().unwrap_err()));
+    }
+}
@@ -0,0 +1,202 @@
+use std::{path::{Path, PathBuf}, fs::{self, OpenOptions}, io::Write};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::{registry, runner};
+
+const MAIN_RS: &str = "src/main.rs";
+const REGISTRY_RS: &str = "src/registry.rs";
+
+/// Root of the source tree, so that days can be created from any directory
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Source of a new day, `{N}` is replaced with the day number
+const TEMPLATE: &str = r#"use std::io::{Read, BufReader, BufRead};
+
+use aoc::{info, diag::Diag, error::{ParseError, ParseResult}, solver::{Solver, Description, Context, Answer, SolverError, Part}};
+
+pub fn load_data(input: impl Read) -> ParseResult<Vec<String>> {
+    BufReader::new(input)
+        .lines()
+        .filter(|l| !matches!(l, Ok(l) if l.trim().is_empty()))
+        .map(|l| l.map_err(ParseError::new))
+        .collect()
+}
+
+pub fn part_1(lines: &[String], diag: &Diag) -> Option<usize> {
+    info!(diag, "{} lines", lines.len());
+    None
+}
+
+pub struct Day{N};
+
+impl Solver for Day{N} {
+    type Input = Vec<String>;
+
+    const DESCRIPTION: Description = Description {
+        title: "Day {N}",
+        part_1: "Not solved yet",
+        part_2: "Not solved yet",
+    };
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(load_data(input.as_bytes())?)
+    }
+
+    fn part_1(&self, lines: &Self::Input, ctx: &Context) -> anyhow::Result<Answer> {
+        let answer = part_1(lines, &ctx.diag).ok_or(SolverError::Unimplemented(Part::One))?;
+        Ok(answer.to_string())
+    }
+
+    fn part_2(&self, _lines: &Self::Input, _ctx: &Context) -> anyhow::Result<Answer> {
+        Err(SolverError::Unimplemented(Part::Two).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_loading() {
+        let lines = load_data("a\n\nb\n".as_bytes()).unwrap();
+        assert_eq!(lines, vec!["a", "b"]);
+    }
+}
+"#;
+
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    #[error("Day {0} is already registered")]
+    Registered(usize),
+    #[error("Refusing to overwrite {}", .0.display())]
+    Exists(PathBuf),
+    #[error("Could not find where to register the day in {}", .0.display())]
+    NoAnchor(PathBuf),
+}
+
+/// Number of the day mentioned first in `text`, e.g. 12 in `mod day12;`
+fn day_of(text: &str) -> Option<usize> {
+    let rest = text.split("day").nth(1)?;
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    rest[..digits].parse().ok()
+}
+
+/// Insert `line` before the first of the lines starting at `starts` that is about a later day,
+/// or after the last of them
+fn insert_line(source: &str, starts: impl Iterator<Item = usize>, day: usize, line: &str) -> Option<String> {
+    let mut at = None;
+    for start in starts {
+        let end = start + source[start..].find('\n')? + 1;
+        if day_of(&source[start..end]) > Some(day) {
+            at = Some(start);
+            break;
+        }
+        at = Some(end);
+    }
+    let at = at?;
+    Some(format!("{}{}\n{}", &source[..at], line, &source[at..]))
+}
+
+/// Add `mod dayN;` among the day modules, in day order
+fn register_module(source: &str, day: usize) -> Option<String> {
+    let modules = source.match_indices("\nmod day").map(|(i, _)| i + 1);
+    insert_line(source, modules, day, &format!("mod day{};", day))
+}
+
+/// Import the module and add the solver to `SOLVERS`, both in day order
+fn register_solver(source: &str, day: usize) -> Option<String> {
+    let import = source.find("use crate::{day")? + "use crate::{".len();
+    let import_end = import + source[import..].find("};")?;
+    let mut modules: Vec<_> = source[import..import_end].split(", ").collect();
+    let module = format!("day{}", day);
+    let position = modules.iter().position(|m| day_of(m) > Some(day)).unwrap_or(modules.len());
+    modules.insert(position, &module);
+    let source = format!("{}{}{}", &source[..import], modules.join(", "), &source[import_end..]);
+
+    let solvers = source.find("const SOLVERS")?;
+    let solvers_end = solvers + source[solvers..].find("];")?;
+    let entries = source[solvers..solvers_end].match_indices("\n    (").map(|(i, _)| solvers + i + 1);
+    insert_line(&source, entries, day, &format!("    ({day}, &day{day}::Day{day}),", day = day))
+}
+
+fn create_new(path: &Path, content: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+fn update(path: &Path, f: impl FnOnce(&str) -> Option<String>) -> Result<()> {
+    let source = fs::read_to_string(path)?;
+    let updated = f(&source).ok_or_else(|| ScaffoldError::NoAnchor(path.to_path_buf()))?;
+    fs::write(path, updated)?;
+    Ok(())
+}
+
+/// Create source and input files of a new day and register it
+///
+/// Files are placed in the source tree regardless of the current directory.
+/// Nothing is written if any of the files already exists.
+/// Returns paths of all created and modified files.
+pub fn new_day(day: usize) -> Result<Vec<PathBuf>> {
+    if registry::get(day).is_some() {
+        Err(ScaffoldError::Registered(day))?;
+    }
+    let source = root().join("src").join(format!("day{}.rs", day));
+    let inputs = ["example", runner::DEFAULT_INPUT].map(|name| root().join(runner::input_path(day, name)));
+    if let Some(existing) = inputs.iter().chain([&source]).find(|p| p.exists()) {
+        Err(ScaffoldError::Exists(existing.clone()))?;
+    }
+
+    // check the anchors before creating anything
+    let main_rs = root().join(MAIN_RS);
+    let registry_rs = root().join(REGISTRY_RS);
+    register_module(&fs::read_to_string(&main_rs)?, day)
+        .ok_or_else(|| ScaffoldError::NoAnchor(main_rs.clone()))?;
+    register_solver(&fs::read_to_string(&registry_rs)?, day)
+        .ok_or_else(|| ScaffoldError::NoAnchor(registry_rs.clone()))?;
+
+    create_new(&source, &TEMPLATE.replace("{N}", &day.to_string()))?;
+    fs::create_dir_all(inputs[0].parent().unwrap())?;
+    for input in &inputs {
+        create_new(input, "")?;
+    }
+    update(&main_rs, |s| register_module(s, day))?;
+    update(&registry_rs, |s| register_solver(s, day))?;
+
+    Ok([source].into_iter().chain(inputs).chain([main_rs, registry_rs]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration() {
+        let main = "mod output;\nmod day1;\nmod day2;\n\nfn main() {}\n";
+        assert_eq!(register_module(main, 3).unwrap(),
+            "mod output;\nmod day1;\nmod day2;\nmod day3;\n\nfn main() {}\n");
+
+        let registry = "use crate::{day1, day2};\n\nconst SOLVERS: &[(usize, SolverRef)] = &[\n    (1, &day1::Day1),\n    (2, &day2::Day2),\n];\n";
+        assert_eq!(register_solver(registry, 3).unwrap(),
+            "use crate::{day1, day2, day3};\n\nconst SOLVERS: &[(usize, SolverRef)] = &[\n    (1, &day1::Day1),\n    (2, &day2::Day2),\n    (3, &day3::Day3),\n];\n");
+    }
+
+    #[test]
+    fn registration_in_day_order() {
+        let main = "mod output;\nmod day1;\nmod day10;\n\nfn main() {}\n";
+        assert_eq!(register_module(main, 2).unwrap(),
+            "mod output;\nmod day1;\nmod day2;\nmod day10;\n\nfn main() {}\n");
+
+        let registry = "use crate::{day1, day10};\n\nconst SOLVERS: &[(usize, SolverRef)] = &[\n    (1, &day1::Day1),\n    (10, &day10::Day10),\n];\n";
+        assert_eq!(register_solver(registry, 2).unwrap(),
+            "use crate::{day1, day2, day10};\n\nconst SOLVERS: &[(usize, SolverRef)] = &[\n    (1, &day1::Day1),\n    (2, &day2::Day2),\n    (10, &day10::Day10),\n];\n");
+    }
+}
@@ -0,0 +1,215 @@
+use std::{net::{TcpListener, TcpStream, SocketAddr, ToSocketAddrs}, io::{self, BufRead, BufReader, Read, Write}, thread, time::Duration};
+
+use aoc::solver::{Part, SolverError};
+
+use crate::{registry, runner::{self, RunConfig, RunError}, output::json_string};
+
+/// Larger requests are rejected, puzzle inputs are much smaller
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+/// Clients that send nothing for this long are answered with 408 and disconnected
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Self {
+        Self { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, format!("{{\"error\": {}}}", json_string(message)))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status, self.reason(), self.body.len(), self.body)?;
+        out.flush()
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let bad_request = |e: io::Error| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Response::error(408, "Timed out reading the request"),
+        _ => Response::error(400, &e.to_string()),
+    };
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(bad_request)?;
+    let mut tokens = line.split_whitespace();
+    let (method, path) = match (tokens.next(), tokens.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(Response::error(400, "Malformed request line")),
+    };
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(bad_request)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse()
+                    .map_err(|_| Response::error(400, "Invalid Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Response::error(413, "Input is too large"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(bad_request)?;
+    let body = String::from_utf8(body)
+        .map_err(|_| Response::error(400, "Input is not valid UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+/// Solve a part of given day, the path looks like `/day/{n}/part/{p}`
+fn solve(path: &str, input: &str, config: &RunConfig) -> Response {
+    let segments: Vec<_> = path.trim_matches('/').split('/').collect();
+    let (day, part) = match segments[..] {
+        ["day", day, "part", part] => (day, part),
+        _ => return Response::error(404, "Expected /day/{n}/part/{p}"),
+    };
+    let (day, solver) = match day.parse().ok().and_then(|n| Some((n, registry::get(n)?))) {
+        Some(found) => found,
+        None => return Response::error(404, &format!("Unknown day: {}", day)),
+    };
+    let part: Part = match part.parse() {
+        Ok(part) => part,
+        Err(e) => return Response::error(404, &format!("{}", e)),
+    };
+
+    let config = RunConfig { parts: vec![part], ..config.clone() };
+    let run = match runner::run(solver, input, &config) {
+        Ok(run) => run,
+        Err(e) if RunError::is_panic(&e) => return Response::error(500, &format!("{:#}", e)),
+        Err(e) => return Response::error(422, &format!("{:#}", runner::with_day(e, day))),
+    };
+    let result = run.part(part).expect("Selected part is run");
+    match &result.answer {
+        Ok(answer) => Response::json(200, format!("{{\"day\": {}, \"part\": {}, \"answer\": {}, \"elapsed_s\": {:.9}}}",
+            day, part, json_string(answer), run.elapsed().as_secs_f64())),
+        Err(e) if SolverError::is_unimplemented(e) => Response::error(404, &e.to_string()),
+        Err(e) => Response::error(500, &format!("{:#}", e)),
+    }
+}
+
+fn handle(stream: TcpStream, config: &RunConfig) -> io::Result<()> {
+    let response = match read_request(&stream) {
+        Ok(request) if request.method == "POST" => solve(&request.path, &request.body, config),
+        Ok(_) => Response::error(405, "Only POST is supported"),
+        Err(response) => response,
+    };
+    response.write_to(&stream)
+}
+
+/// HTTP interface to the solvers, `POST /day/{n}/part/{p}` with the input as the body
+pub struct Server {
+    listener: TcpListener,
+    config: RunConfig,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs, config: RunConfig) -> io::Result<Self> {
+        Ok(Self { listener: TcpListener::bind(addr)?, config })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handle connections forever, each on its own thread
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            // a failed connection should not stop the server
+            let stream = match stream.and_then(|stream| stream.set_read_timeout(Some(READ_TIMEOUT)).map(|_| stream)) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Connection failed: {}", e);
+                    continue;
+                },
+            };
+            let config = self.config.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle(stream, &config) {
+                    eprintln!("Connection failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc::diag::Diag;
+
+    fn start() -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0", RunConfig::new(None, Diag::default(), vec![])).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(|| server.serve());
+        addr
+    }
+
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (status, body)
+    }
+
+    #[test]
+    fn answers() {
+        let addr = start();
+        let (status, body) = request(addr, "POST", "/day/1/part/2", "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n");
+        assert_eq!(status, 200);
+        assert!(body.starts_with("{\"day\": 1, \"part\": 2, \"answer\": \"5\""), "{}", body);
+    }
+
+    #[test]
+    fn errors() {
+        let addr = start();
+        assert_eq!(request(addr, "POST", "/day/99/part/1", "").0, 404);
+        assert_eq!(request(addr, "POST", "/day/1/part/3", "").0, 404);
+        assert_eq!(request(addr, "GET", "/day/1/part/1", "").0, 405);
+        assert_eq!(request(addr, "POST", "/day/8/part/2", "").0, 404);
+        let (status, body) = request(addr, "POST", "/day/13/part/1", "x,1\n\nfold along y=1\n");
+        assert_eq!(status, 422);
+        assert!(body.contains("Day 13, line 1, column 1"), "{}", body);
+        let (status, body) = request(addr, "POST", "/day/13/part/1", "0,1\n\nfold along y=1\n");
+        assert_eq!(status, 422);
+        assert!(body.contains("Day 13, line 3: Fold line goes through a dot"), "{}", body);
+    }
+}
@@ -0,0 +1,296 @@
+use std::{any::Any, fmt::{Debug, Display}, str::FromStr, collections::BTreeMap, ops::RangeBounds};
+
+use anyhow::Result;
+use thiserror::Error;
+
+use crate::diag::Diag;
+
+/// Answer to a single part of a puzzle
+pub type Answer = String;
+
+/// Input parsed by a type-erased solver
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = SolverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            other => Err(SolverError::UnknownPart(other.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SolverError {
+    #[error("Part {0} is not implemented")]
+    Unimplemented(Part),
+    #[error("Unknown part: {0} (expected 1 or 2)")]
+    UnknownPart(String),
+    #[error("Parsed input does not match the solver")]
+    InputMismatch,
+}
+
+#[derive(Error, Debug)]
+pub enum ParamError {
+    #[error("Expected 'key=value' but got: {0}")]
+    Malformed(String),
+    #[error("Unknown parameter '{name}', available: {}", .available.join(", "))]
+    Unknown {
+        name: String,
+        available: Vec<String>,
+    },
+    #[error("Invalid value '{value}' for parameter '{name}': {reason}")]
+    Invalid {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl SolverError {
+    /// Check if the error only means that given part has not been implemented
+    pub fn is_unimplemented(err: &anyhow::Error) -> bool {
+        matches!(err.downcast_ref::<SolverError>(), Some(SolverError::Unimplemented(_)))
+    }
+}
+
+/// What the puzzle of a day is about
+#[derive(Debug, Clone, Copy)]
+pub struct Description {
+    pub title: &'static str,
+    /// What the answer to part 1 is
+    pub part_1: &'static str,
+    /// What the answer to part 2 is
+    pub part_2: &'static str,
+}
+
+/// Tunable puzzle parameter declared by a solver
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+/// Parameter value given on the command line as `key=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamValue {
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for ParamValue {
+    type Err = ParamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok(Self {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+            }),
+            _ => Err(ParamError::Malformed(s.to_string())),
+        }
+    }
+}
+
+/// Values of the parameters declared by a solver, defaults overridden by given values
+#[derive(Debug, Clone, Default)]
+pub struct Params(BTreeMap<&'static str, String>);
+
+impl Params {
+    /// Use values of the declared parameters, values of other parameters are ignored
+    pub fn new(declared: &'static [Param], values: &[ParamValue]) -> Self {
+        let params = declared.iter()
+            .map(|param| {
+                let value = values.iter()
+                    .rfind(|v| v.name == param.name)
+                    .map_or(param.default, |v| &v.value);
+                (param.name, value.to_string())
+            })
+            .collect();
+        Self(params)
+    }
+
+    /// Fail for values of parameters which are not declared
+    pub fn check(declared: &[Param], values: &[ParamValue]) -> Result<(), ParamError> {
+        match values.iter().find(|v| !declared.iter().any(|p| p.name == v.name)) {
+            Some(unknown) => Err(ParamError::Unknown {
+                name: unknown.name.clone(),
+                available: declared.iter().map(|p| p.name.to_string()).collect(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse value of given parameter
+    pub fn get<T>(&self, name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.0.get(name).ok_or_else(|| ParamError::Unknown {
+            name: name.to_string(),
+            available: self.0.keys().map(|k| k.to_string()).collect(),
+        })?;
+        value.parse().map_err(|e: T::Err| ParamError::Invalid {
+            name: name.to_string(),
+            value: value.clone(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Parse value of given parameter, failing if it is out of `range`
+    pub fn get_in<T, R>(&self, name: &str, range: R) -> Result<T, ParamError>
+    where
+        T: FromStr + PartialOrd,
+        T::Err: Display,
+        R: RangeBounds<T> + Debug,
+    {
+        let value = self.get(name)?;
+        if range.contains(&value) {
+            return Ok(value);
+        }
+        Err(ParamError::Invalid {
+            name: name.to_string(),
+            value: self.0[name].clone(),
+            reason: format!("expected a value in {:?}", range),
+        })
+    }
+}
+
+/// Everything a solver gets besides its input
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub diag: Diag,
+    pub params: Params,
+}
+
+impl Context {
+    /// Parse value of given parameter, see `Params::get`
+    pub fn param<T>(&self, name: &str) -> Result<T, ParamError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.params.get(name)
+    }
+
+    /// Parse value of given parameter within `range`, see `Params::get_in`
+    pub fn param_in<T, R>(&self, name: &str, range: R) -> Result<T, ParamError>
+    where
+        T: FromStr + PartialOrd,
+        T::Err: Display,
+        R: RangeBounds<T> + Debug,
+    {
+        self.params.get_in(name, range)
+    }
+}
+
+/// Solver of a single day's puzzle
+///
+/// Input is parsed once and then shared by both parts.
+pub trait Solver {
+    type Input: Send + Sync + 'static;
+
+    const DESCRIPTION: Description;
+
+    /// Parameters which can be overridden with `--param key=value`
+    const PARAMS: &'static [Param] = &[];
+
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+    fn part_1(&self, input: &Self::Input, ctx: &Context) -> Result<Answer>;
+    fn part_2(&self, input: &Self::Input, ctx: &Context) -> Result<Answer>;
+}
+
+/// Type-erased `Solver` so that solvers of all days can be stored together
+pub trait DynSolver {
+    fn description(&self) -> Description;
+    fn params(&self) -> &'static [Param];
+    fn load(&self, input: &str) -> Result<Parsed>;
+    fn solve(&self, input: &Parsed, part: Part, ctx: &Context) -> Result<Answer>;
+}
+
+impl<S: Solver> DynSolver for S {
+    fn description(&self) -> Description {
+        S::DESCRIPTION
+    }
+
+    fn params(&self) -> &'static [Param] {
+        S::PARAMS
+    }
+
+    fn load(&self, input: &str) -> Result<Parsed> {
+        Ok(Box::new(self.parse(input)?))
+    }
+
+    fn solve(&self, input: &Parsed, part: Part, ctx: &Context) -> Result<Answer> {
+        let input = input.downcast_ref::<S::Input>()
+            .ok_or(SolverError::InputMismatch)?;
+        match part {
+            Part::One => self.part_1(input, ctx),
+            Part::Two => self.part_2(input, ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param { name: "days", default: "80", help: "" },
+        Param { name: "tiles", default: "5", help: "" },
+    ];
+
+    fn values(values: &[&str]) -> Vec<ParamValue> {
+        values.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn param_values() {
+        assert_eq!("days = 10".parse::<ParamValue>().unwrap(), ParamValue { name: "days".to_string(), value: "10".to_string() });
+        assert!(matches!("days".parse::<ParamValue>(), Err(ParamError::Malformed(_))));
+        assert!(matches!("=10".parse::<ParamValue>(), Err(ParamError::Malformed(_))));
+    }
+
+    #[test]
+    fn overrides() {
+        let params = Params::new(PARAMS, &values(&["days=1", "other=2", "days=3"]));
+        assert_eq!(params.get::<usize>("days").unwrap(), 3);
+        assert_eq!(params.get::<usize>("tiles").unwrap(), 5);
+        assert!(matches!(params.get::<usize>("other"), Err(ParamError::Unknown { .. })));
+        assert!(Params::check(PARAMS, &values(&["tiles=2"])).is_ok());
+        assert!(matches!(Params::check(PARAMS, &values(&["other=2"])), Err(ParamError::Unknown { .. })));
+    }
+
+    #[test]
+    fn ranges() {
+        let params = Params::new(PARAMS, &values(&["days=-1", "tiles=0"]));
+        assert!(matches!(params.get::<usize>("days"), Err(ParamError::Invalid { .. })));
+        assert_eq!(params.get_in::<i32, _>("days", ..0).unwrap(), -1);
+        let e = params.get_in::<usize, _>("tiles", 1..).unwrap_err();
+        assert_eq!(e.to_string(), "Invalid value '0' for parameter 'tiles': expected a value in 1..");
+    }
+}
@@ -0,0 +1,291 @@
+use std::{fmt::Display, fs::{self, OpenOptions}, io::{self, Write}, path::{Path, PathBuf}, str::FromStr, thread, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use anyhow::{Result, Context};
+use regex::Regex;
+use thiserror::Error;
+
+use aoc::solver::Part;
+
+use crate::{fetch::Client, runner};
+
+/// What the puzzle site said about a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    RateLimited,
+}
+
+impl Verdict {
+    const ALL: [Verdict; 5] = [Verdict::Correct, Verdict::Wrong, Verdict::TooHigh, Verdict::TooLow, Verdict::RateLimited];
+
+    /// Name used in the submissions file
+    fn name(self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::RateLimited => "rate-limited",
+        }
+    }
+
+    /// The answer was checked and is not the right one
+    fn is_wrong(self) -> bool {
+        matches!(self, Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow)
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::RateLimited => write!(f, "not checked, answered too recently"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Verdict::ALL.into_iter()
+            .find(|verdict| verdict.name() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown verdict: {}", s))
+    }
+}
+
+/// Verdict together with how long to wait before the next submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub verdict: Verdict,
+    pub wait: Duration,
+}
+
+#[derive(Error, Debug)]
+pub enum SubmitError {
+    #[error("Answer {answer} was already submitted, it is {verdict}")]
+    AlreadyWrong {
+        answer: String,
+        verdict: Verdict,
+    },
+    #[error("Part is already solved with answer {0}")]
+    AlreadySolved(String),
+    #[error("Wait {}s before submitting again (or use --wait)", .0.as_secs())]
+    Cooldown(Duration),
+    #[error("Unexpected response: {0}")]
+    Unexpected(String),
+}
+
+/// Text of the main article of a response page
+fn article_text(html: &str) -> String {
+    let article = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+    let text = article.captures(html).map_or(html, |caps| caps.get(1).unwrap().as_str());
+    tag.replace_all(text, "").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Wait mentioned in the text, like "You have 1m 5s left to wait" or "please wait 5 minutes"
+fn parse_wait(text: &str) -> Duration {
+    let left = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+    let minutes = Regex::new(r"wait (one|\d+) minutes?").unwrap();
+    if let Some(caps) = left.captures(text) {
+        let m: u64 = caps.get(1).map_or(0, |m| m.as_str().parse().unwrap());
+        let s: u64 = caps[2].parse().unwrap();
+        Duration::from_secs(m * 60 + s)
+    } else if let Some(caps) = minutes.captures(text) {
+        let m: u64 = caps[1].parse().unwrap_or(1);
+        Duration::from_secs(m * 60)
+    } else {
+        Duration::ZERO
+    }
+}
+
+/// Interpret the page returned after submitting an answer
+pub fn parse_response(html: &str) -> Result<Outcome, SubmitError> {
+    let text = article_text(html);
+    let verdict = if text.contains("That's the right answer") {
+        Verdict::Correct
+    } else if text.contains("answer too recently") {
+        Verdict::RateLimited
+    } else if text.contains("That's not the right answer") {
+        if text.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if text.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else {
+        return Err(SubmitError::Unexpected(text));
+    };
+    Ok(Outcome { verdict, wait: parse_wait(&text) })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// A submitted answer, stored as `<unix time> <part> <verdict> <wait in s> <answer>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Submission {
+    time: u64,
+    part: Part,
+    outcome: Outcome,
+    answer: String,
+}
+
+impl Display for Submission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} {} {}", self.time, self.part, self.outcome.verdict.name(), self.outcome.wait.as_secs(), self.answer)
+    }
+}
+
+impl FromStr for Submission {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.splitn(5, ' ').collect();
+        match fields[..] {
+            [time, part, verdict, wait, answer] => Ok(Submission {
+                time: time.parse()?,
+                part: part.parse()?,
+                outcome: Outcome { verdict: verdict.parse()?, wait: Duration::from_secs(wait.parse()?) },
+                answer: answer.to_string(),
+            }),
+            _ => anyhow::bail!("Expected <time> <part> <verdict> <wait> <answer>"),
+        }
+    }
+}
+
+/// Answers submitted for a day, kept in `data/NN/submissions`
+pub struct Submissions {
+    path: PathBuf,
+    entries: Vec<Submission>,
+}
+
+impl Submissions {
+    pub fn path(day: usize) -> PathBuf {
+        runner::input_path(day, "submissions")
+    }
+
+    /// Read submissions from `path`, none if it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Cannot read {}", path.display())),
+        };
+        let entries = contents.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| line.parse()
+                .with_context(|| format!("{}:{}: invalid submission '{}'", path.display(), i + 1, line)))
+            .collect::<Result<_>>()?;
+        Ok(Self { path: path.to_path_buf(), entries })
+    }
+
+    /// Fail if the answer is known to be wrong or the part is solved already
+    fn check(&self, part: Part, answer: &str) -> Result<(), SubmitError> {
+        for entry in self.entries.iter().filter(|entry| entry.part == part) {
+            match entry.outcome.verdict {
+                Verdict::Correct => return Err(SubmitError::AlreadySolved(entry.answer.clone())),
+                verdict if verdict.is_wrong() && entry.answer == answer =>
+                    return Err(SubmitError::AlreadyWrong { answer: answer.to_string(), verdict }),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Time left until another answer may be submitted
+    fn cooldown(&self, now: u64) -> Duration {
+        let until = self.entries.iter()
+            .map(|entry| entry.time + entry.outcome.wait.as_secs())
+            .max()
+            .unwrap_or(0);
+        Duration::from_secs(until.saturating_sub(now))
+    }
+
+    fn record(&mut self, submission: Submission) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", submission)?;
+        self.entries.push(submission);
+        Ok(())
+    }
+}
+
+/// Submit answer of given day and part unless it is known to be wrong
+///
+/// Waits required by the site are honoured: with `wait` by sleeping, otherwise
+/// by failing with `SubmitError::Cooldown` without sending anything.
+pub fn submit(client: &Client, submissions: &mut Submissions, day: usize, part: Part, answer: &str, wait: bool) -> Result<Outcome> {
+    if answer.is_empty() || answer.contains('\n') {
+        anyhow::bail!("Answer must be a single line, got {:?}", answer);
+    }
+    submissions.check(part, answer)?;
+    let cooldown = submissions.cooldown(unix_now());
+    if !cooldown.is_zero() {
+        if !wait {
+            Err(SubmitError::Cooldown(cooldown))?;
+        }
+        println!("Waiting {}s before submitting", cooldown.as_secs());
+        thread::sleep(cooldown);
+    }
+
+    let level = part.to_string();
+    let html = client.send("POST", &client.day_url(day, "/answer"), &[("level", &level), ("answer", answer)])?;
+    let outcome = parse_response(&html)?;
+    submissions.record(Submission { time: unix_now(), part, outcome, answer: answer.to_string() })
+        .with_context(|| format!("Cannot record submission in {}", submissions.path.display()))?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::tests::stand_in;
+
+    const TOO_LOW: &str = "<main><article><p>That's not the right answer; your answer is too low. \
+        If you're stuck, make sure you're using the full input data; please wait one minute before trying again. \
+        <a href=\"/2021/day/7\">[Return to Day 7]</a></p></article></main>";
+    const TOO_RECENT: &str = "<main><article><p>You gave an answer too recently; you have to wait after submitting \
+        an answer before trying again.  You have 1m 5s left to wait. <a href=\"/2021/day/7\">[Return to Day 7]</a></p></article></main>";
+
+    #[test]
+    fn responses() {
+        assert_eq!(parse_response(TOO_LOW).unwrap(), Outcome { verdict: Verdict::TooLow, wait: Duration::from_secs(60) });
+        assert_eq!(parse_response(TOO_RECENT).unwrap(), Outcome { verdict: Verdict::RateLimited, wait: Duration::from_secs(65) });
+        assert_eq!(parse_response("<article><p>That's the right answer! You are <em>one gold star</em> closer.</p></article>").unwrap().verdict,
+            Verdict::Correct);
+        assert!(matches!(parse_response("<article><p>You don't seem to be solving the right level.</p></article>"),
+            Err(SubmitError::Unexpected(_))));
+    }
+
+    #[test]
+    fn remembers_submissions() {
+        let path = std::env::temp_dir().join(format!("aoc-submissions-{}", std::process::id()));
+        let mut submissions = Submissions::load(&path).unwrap();
+        let (url, server) = stand_in("200 OK", TOO_LOW);
+        let client = Client::new(&url, "secret".to_string());
+
+        let outcome = submit(&client, &mut submissions, 7, Part::Two, "1234", false).unwrap();
+        assert_eq!(outcome.verdict, Verdict::TooLow);
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2021/day/7/answer "), "{}", request);
+        assert!(request.ends_with("level=2&answer=1234"), "{}", request);
+
+        // neither the same answer nor another one during the wait reach the server
+        let mut submissions = Submissions::load(&path).unwrap();
+        let e = submit(&client, &mut submissions, 7, Part::Two, "1234", false).unwrap_err();
+        assert!(matches!(e.downcast_ref(), Some(SubmitError::AlreadyWrong { verdict: Verdict::TooLow, .. })), "{}", e);
+        let e = submit(&client, &mut submissions, 7, Part::Two, "4321", false).unwrap_err();
+        assert!(matches!(e.downcast_ref(), Some(SubmitError::Cooldown(_))), "{}", e);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,34 +1,42 @@
-use std::{io::{BufRead, self, BufReader}, str::FromStr, error::Error, path::Path, fs::File};
+use std::{io::{BufRead, self, BufReader}, str::FromStr, error::Error, fmt::Display, path::Path, fs::File};
+
+use crate::{error::{ParseError, ParseErrors, ParseResult}, grid::Grid};
 
 /// Parse lines from input
 ///
 /// Returns an iterator over parse results of non-empty lines in input.
-pub fn parse_lines<I, T, E>(input: I) -> impl Iterator<Item = io::Result<T>>
+/// Errors point at the line by its 1-based number and text.
+pub fn parse_lines<I, T, E>(input: I) -> impl Iterator<Item = ParseResult<T>>
     where
         I: BufRead,
         E: Into<Box<dyn Error + Send + Sync>>,
         T: FromStr<Err = E>,
 {
     input.lines()
-        .filter_map(|line| {
+        .enumerate()
+        .filter_map(|(i, line)| {
             // pass errors
             let line = match line {
                 Ok(line) => line,
-                Err(e) => return Some(Err(e))
+                Err(e) => return Some(Err(ParseError { line: Some(i + 1), ..ParseError::new(e) })),
             };
             // omit empty lines
-            let line = line.trim();
-            if line.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
                 return None;
             }
-            // parse lines, comvert errors to io::Error
-            let result = line.parse()
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+            // parse lines, add the untrimmed line to errors and shift their columns to match it
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+            let result = trimmed.parse()
+                .map_err(|e: E| {
+                    let mut error = ParseError::in_line(i + 1, &line, e.into());
+                    error.column = error.column.map(|column| column + indent);
+                    error
+                });
             Some(result)
         })
 }
 
-
 /// Load and parse lines from file
 ///
 /// Wrapper around `parse_lines` that reads from file
@@ -42,12 +50,417 @@ pub fn load_from_file<P, T, E>(path: P) -> io::Result<Vec<T>>
     let file = File::open(path)?;
     let input = BufReader::new(file);
     parse_lines(input)
+        .collect::<ParseResult<_>>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Parse lines from a string
+///
+/// Wrapper around `parse_lines` that collects the parsed results into a vector,
+/// stopping at the first invalid line.
+pub fn parse_str<T, E>(input: &str) -> ParseResult<Vec<T>>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+        T: FromStr<Err = E>,
+{
+    parse_lines(input.as_bytes())
+        .collect()
+}
+
+/// Parse lines from a string, reporting all invalid lines
+///
+/// Like `parse_str`, but continues past invalid lines to collect all their errors.
+pub fn parse_str_all<T, E>(input: &str) -> Result<Vec<T>, ParseErrors>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+        T: FromStr<Err = E>,
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for result in parse_lines(input.as_bytes()) {
+        match result {
+            Ok(value) => values.push(value),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(ParseErrors(errors))
+    }
+}
+
+/// Non-empty lines with their 1-based line numbers
+pub fn numbered_lines<S: AsRef<str>>(lines: &[S]) -> impl Iterator<Item = (usize, &str)> {
+    lines.iter()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.as_ref().trim_end()))
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+/// Block of consecutive non-empty lines, separated from others by blank lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// 1-based number of the first line
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    /// Lines of the section with their 1-based line numbers in the whole input
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &'a str)> {
+        let first = self.line;
+        self.text.lines()
+            .enumerate()
+            .map(move |(i, line)| (first + i, line))
+    }
+
+    /// Parse the whole section as a single value
+    ///
+    /// Line numbers of `ParseError`s are taken as relative to the section,
+    /// other errors point at the first line of the section.
+    pub fn parse<T, E>(&self) -> ParseResult<T>
+        where
+            E: Into<Box<dyn Error + Send + Sync>>,
+            T: FromStr<Err = E>,
+    {
+        self.text.parse().map_err(|e: E| match e.into().downcast::<ParseError>() {
+            Ok(error) => self.locate(*error),
+            Err(error) => ParseError::new(error).line(self.line, self.first_line()),
+        })
+    }
+
+    /// Parse each line of the section
+    pub fn parse_lines<T, E>(&self) -> ParseResult<Vec<T>>
+        where
+            E: Into<Box<dyn Error + Send + Sync>>,
+            T: FromStr<Err = E>,
+    {
+        parse_lines(self.text.as_bytes())
+            .map(|result| result.map_err(|e| self.locate(e)))
+            .collect()
+    }
+
+    fn first_line(&self) -> &'a str {
+        self.text.lines().next().unwrap_or_default()
+    }
+
+    /// Move error from a line within the section to the line within the whole input
+    fn locate(&self, mut error: ParseError) -> ParseError {
+        match error.line {
+            Some(line) => error.line = Some(self.line + line - 1),
+            None => error = error.line(self.line, self.first_line()),
+        }
+        error
+    }
+}
+
+/// Split input into blocks of lines separated by blank lines
+pub fn sections(input: &str) -> impl Iterator<Item = Section<'_>> {
+    let mut sections = Vec::new();
+    // line number and offset of the first line of current section
+    let mut start = None;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((first, begin)) = start.take() {
+                sections.push(Section { line: first, text: input[begin..offset].trim_end() });
+            }
+        } else if start.is_none() {
+            start = Some((i + 1, offset));
+        }
+        offset += line.len();
+    }
+    if let Some((first, begin)) = start {
+        sections.push(Section { line: first, text: input[begin..].trim_end() });
+    }
+    sections.into_iter()
+}
+
+/// Split input into exactly `N` sections separated by blank lines
+pub fn split_sections<const N: usize>(input: &str) -> ParseResult<[Section<'_>; N]> {
+    let sections: Vec<_> = sections(input).collect();
+    let error = ParseError::new(format!("Expected {} sections separated by blank lines, got {}", N, sections.len()));
+    match sections.get(N) {
+        Some(extra) => Err(error.line(extra.line, extra.first_line())),
+        None => sections.try_into().map_err(|_| error),
+    }
+}
+
+/// Parse a line of comma-separated values, like `3,4,3,1,2`
+///
+/// Errors point at the column of the invalid value, the line number is up to the caller.
+pub fn parse_comma_separated<T>(line: &str) -> ParseResult<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+{
+    line.split(',')
+        .map(|value| value.trim().parse()
+            .map_err(|e| ParseError::new(format!("Invalid value: {}", e)).within(line, value)))
         .collect()
 }
 
+/// Parse a grid with a cell per character, one row per non-empty line
+///
+/// `cell` converts a character or describes why it is not valid.
+/// All rows have to be of the same width.
+pub fn parse_grid<T, E, F>(input: &str, cell: F) -> ParseResult<Grid<T>>
+    where
+        T: Copy,
+        E: Display,
+        F: Fn(char) -> Result<T, E>,
+{
+    let lines: Vec<_> = input.lines().collect();
+    let mut width: Option<usize> = None;
+    let mut cells = Vec::new();
+    for (number, line) in numbered_lines(&lines) {
+        let line = line.trim_start();
+        for (i, c) in line.char_indices() {
+            let value = cell(c).map_err(|e| ParseError::new(e).at(number, line, &line[i..]))?;
+            cells.push(value);
+        }
+        let count = line.chars().count();
+        match width {
+            Some(width) if width != count => {
+                return Err(ParseError::new(format!("Expected {} columns like the first row, got {}", width, count))
+                    .line(number, line)
+                    .column(width.min(count) + 1));
+            },
+            _ => width = Some(count),
+        }
+    }
+    let width = width.ok_or_else(|| ParseError::new("Empty grid"))?;
+    Ok(Grid::from(cells, width).expect("All rows have the same width"))
+}
+
+/// Parse a grid of characters
+pub fn parse_char_grid(input: &str) -> ParseResult<Grid<char>> {
+    parse_grid(input, Ok::<_, ParseError>)
+}
+
+/// Parse a grid of decimal digits, like `2199943210`
+pub fn parse_digit_grid<T: From<u8> + Copy>(input: &str) -> ParseResult<Grid<T>> {
+    parse_grid(input, |c| c.to_digit(10)
+        .map(|digit| T::from(digit as u8))
+        .ok_or_else(|| format!("Expected a digit 0-9, got '{}'", c)))
+}
+
+/// Line format with named placeholders, like `{x1},{y1} -> {x2},{y2}`
+///
+/// A placeholder matches the text up to the first occurrence of the text following it,
+/// or up to the end of the line. Placeholders have to be separated by some text.
+/// As that match is greedy, `scan_with` can check each field before the next one is matched.
+#[derive(Debug, Clone)]
+pub struct Template<'t> {
+    /// Text before the first placeholder
+    prefix: &'t str,
+    /// Names of placeholders with the text following them
+    fields: Vec<(&'t str, &'t str)>,
+}
+
+impl<'t> Template<'t> {
+    /// Panics on unbalanced braces or placeholders next to each other
+    pub fn new(template: &'t str) -> Self {
+        let (prefix, mut rest) = template.split_once('{').unwrap_or((template, ""));
+        let mut fields = Vec::new();
+        while !rest.is_empty() {
+            let (name, after) = rest.split_once('}')
+                .unwrap_or_else(|| panic!("Unclosed placeholder in template: {}", template));
+            let (text, next) = after.split_once('{').unwrap_or((after, ""));
+            assert!(!text.is_empty() || next.is_empty(), "Placeholders without text between them in template: {}", template);
+            fields.push((name, text));
+            rest = next;
+        }
+        Self { prefix, fields }
+    }
+
+    /// Match a line, the fields can be parsed with `Fields::get`
+    pub fn scan<'l>(&self, line: &'l str) -> ParseResult<Fields<'t, 'l>> {
+        self.scan_with(line, |_, _| Ok(()))
+    }
+
+    /// Match a line, calling `check` with the name of each field as soon as it is matched
+    ///
+    /// Errors from `check` take precedence over text missing later in the line,
+    /// so an invalid field is reported instead of the literal it swallowed.
+    pub fn scan_with<'l, F>(&self, line: &'l str, mut check: F) -> ParseResult<Fields<'t, 'l>>
+        where F: FnMut(&Fields<'t, 'l>, &str) -> ParseResult<()>
+    {
+        let text = line.trim();
+        let expected = |rest: &str, literal: &str, after: Option<&str>| {
+            let reason = match after {
+                Some(name) => format!("Expected '{}' after {}", literal, name),
+                None => format!("Expected '{}'", literal),
+            };
+            ParseError::new(reason).within(line, rest)
+        };
+
+        let mut rest = text.strip_prefix(self.prefix)
+            .ok_or_else(|| expected(text, self.prefix, None))?;
+        let mut fields = Fields { line, values: Vec::with_capacity(self.fields.len()) };
+        for (name, literal) in &self.fields {
+            let end = match literal.is_empty() {
+                true => rest.len(),
+                false => rest.find(literal).ok_or_else(|| expected(rest, literal, Some(name)))?,
+            };
+            fields.values.push((*name, &rest[..end]));
+            check(&fields, name)?;
+            rest = &rest[end + literal.len()..];
+        }
+        if !rest.is_empty() {
+            return Err(ParseError::new("Unexpected text at the end").within(line, rest));
+        }
+        Ok(fields)
+    }
+}
+
+/// Text of placeholders matched by a `Template`
+#[derive(Debug, Clone)]
+pub struct Fields<'t, 'l> {
+    line: &'l str,
+    values: Vec<(&'t str, &'l str)>,
+}
+
+impl Fields<'_, '_> {
+    /// Parse field of given name, errors name the field
+    pub fn get<T>(&self, name: &str) -> ParseResult<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+    {
+        let value = self.values.iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| ParseError::new(format!("No field {} in template", name)))?;
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Err(ParseError::new(format!("Missing {}", name)).within(self.line, value));
+        }
+        trimmed.parse()
+            .map_err(|e| ParseError::new(format!("Invalid {}: {}", name, e)).within(self.line, trimmed))
+    }
+}
+
+/// Scan a line with a `Template` and parse its fields as given types into a tuple
+///
+/// `scan!(line, "{x},{y}", x: usize, y: usize)` gives `ParseResult<(usize, usize)>`.
+/// Errors point at the column within the line, the line number is up to the caller.
+/// Each field is parsed as soon as it is matched and the template is only built once.
+#[macro_export]
+macro_rules! scan {
+    ($line:expr, $template:literal, $($name:ident: $type:ty),+ $(,)?) => {{
+        static TEMPLATE: ::std::sync::OnceLock<$crate::utils::Template<'static>> = ::std::sync::OnceLock::new();
+        let line = $line;
+        $(let mut $name: Option<$type> = None;)+
+        TEMPLATE.get_or_init(|| $crate::utils::Template::new($template))
+            .scan_with(line, |fields, field| {
+                $(if field == stringify!($name) {
+                    $name = Some(fields.get(field)?);
+                })+
+                Ok(())
+            })
+            .and_then(|fields| Ok(($(match $name {
+                Some(value) => value,
+                None => fields.get::<$type>(stringify!($name))?,
+            },)+)))
+    }};
+}
+
 /// Load lines from a file
 pub fn load_lines<P>(path: P) -> io::Result<Vec<String>>
     where P: AsRef<Path>
 {
     load_from_file(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_numbers() {
+        let e = parse_str::<usize, _>("1\n\n  x2 \n3\n").unwrap_err();
+        assert_eq!((e.line, e.text.as_str()), (Some(3), "  x2 "));
+        assert_eq!(e.to_string(), "line 3: invalid digit found in string in '  x2 '");
+    }
+
+    #[test]
+    fn indented_columns() {
+        #[derive(Debug)]
+        struct Pair;
+
+        impl FromStr for Pair {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> ParseResult<Self> {
+                crate::scan!(s, "{a},{b}", a: usize, b: usize).map(|_| Pair)
+            }
+        }
+
+        let e = parse_str::<Pair, _>("1,2\n  1,x\n").unwrap_err();
+        assert_eq!((e.line, e.text.as_str(), e.column), (Some(2), "  1,x", Some(5)));
+    }
+
+    #[test]
+    fn all_errors() {
+        assert_eq!(parse_str_all::<usize, _>("1\n2\n").unwrap(), vec![1, 2]);
+        let errors = parse_str_all::<usize, _>("a\n2\n-3\n").unwrap_err();
+        assert_eq!(errors.0.iter().map(|e| e.line).collect::<Vec<_>>(), vec![Some(1), Some(3)]);
+        assert_eq!(errors.to_string(), "line 1: invalid digit found in string in 'a' (and 1 more invalid lines)");
+    }
+
+    #[test]
+    fn blank_line_sections() {
+        let input = "\nNNCB\n\n\nCH -> B\nHH -> x\n  \n";
+        let [template, rules] = split_sections(input).unwrap();
+        assert_eq!(template, Section { line: 2, text: "NNCB" });
+        assert_eq!(rules.lines().collect::<Vec<_>>(), vec![(5, "CH -> B"), (6, "HH -> x")]);
+        let e = rules.parse_lines::<usize, _>().unwrap_err();
+        assert_eq!((e.line, e.text.as_str()), (Some(5), "CH -> B"));
+
+        let e = split_sections::<1>(input).unwrap_err();
+        assert_eq!(e.to_string(), "line 5: Expected 1 sections separated by blank lines, got 2 in 'CH -> B'");
+        assert!(split_sections::<3>(input).is_err());
+    }
+
+    #[test]
+    fn comma_separated() {
+        assert_eq!(parse_comma_separated::<u8>("3,4, 3,1").unwrap(), vec![3, 4, 3, 1]);
+        let e = parse_comma_separated::<u8>("3,4,,1").unwrap_err();
+        assert_eq!((e.reason.as_str(), e.column), ("Invalid value: cannot parse integer from empty string", Some(5)));
+    }
+
+    #[test]
+    fn digit_grid() {
+        let grid: Grid<u8> = parse_digit_grid("123\n456\n").unwrap();
+        assert_eq!((grid.width, grid.height(), grid[(2, 1)]), (3, 2, 6));
+        let e = parse_digit_grid::<u8>("123\n4x6\n").unwrap_err();
+        assert_eq!((e.line, e.column, e.reason.as_str()), (Some(2), Some(2), "Expected a digit 0-9, got 'x'"));
+        let e = parse_digit_grid::<u8>("123\n\n1234\n").unwrap_err();
+        assert_eq!((e.line, e.column), (Some(3), Some(4)));
+        assert_eq!(parse_char_grid("#.\n.#").unwrap()[(1, 1)], '#');
+    }
+
+    #[test]
+    fn scan_template() {
+        let line = "target area: x=20..30, y=-10..-5";
+        let target = crate::scan!(line, "target area: x={x1}..{x2}, y={y1}..{y2}", x1: i32, x2: i32, y1: i32, y2: i32);
+        assert_eq!(target.unwrap(), (20, 30, -10, -5));
+        assert_eq!(crate::scan!("fold along y=7", "fold along {axis}={position}", axis: char, position: usize).unwrap(), ('y', 7));
+    }
+
+    #[test]
+    fn scan_errors() {
+        let e = crate::scan!("3,4 -> 5;6", "{x1},{y1} -> {x2},{y2}", x1: usize, y1: usize, x2: usize, y2: usize).unwrap_err();
+        assert_eq!((e.reason.as_str(), e.column), ("Expected ',' after x2", Some(8)));
+        let e = crate::scan!("3,4 -> 5,z", "{x1},{y1} -> {x2},{y2}", x1: usize, y1: usize, x2: usize, y2: usize).unwrap_err();
+        assert_eq!((e.reason.as_str(), e.column), ("Invalid y2: invalid digit found in string", Some(10)));
+        let e = crate::scan!("AB -> ", "{pair} -> {insertion}", pair: String, insertion: char).unwrap_err();
+        assert_eq!(e.reason, "Expected ' -> ' after pair");
+        let e = crate::scan!("AB -> C D", "{pair} -> {insertion}", pair: String, insertion: char).unwrap_err();
+        assert_eq!(e.reason, "Invalid insertion: too many characters in string");
+        let e = crate::scan!("5;6 -> 1,1", "{x1},{y1} -> {x2},{y2}", x1: usize, y1: usize, x2: usize, y2: usize).unwrap_err();
+        assert_eq!((e.reason.as_str(), e.column), ("Invalid x1: invalid digit found in string", Some(1)));
+    }
+}
@@ -0,0 +1,34 @@
+use std::{path::Path, time::{Duration, SystemTime}, fs, thread, io::{self, Write}, panic::{self, AssertUnwindSafe}};
+
+use anyhow::Result;
+
+/// How often the input file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Call `run` now and after each modification of the file, never returns
+///
+/// Errors and panics of `run` are reported and watching continues.
+pub fn watch(path: &Path, mut run: impl FnMut() -> Result<()>) -> ! {
+    let mut last = None;
+    loop {
+        let current = modified(path);
+        if current.is_some() && current != last {
+            last = current;
+            print!("{}", CLEAR_SCREEN);
+            let _ = io::stdout().flush();
+            match panic::catch_unwind(AssertUnwindSafe(&mut run)) {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => println!("Error: {}", crate::error_report(&e)),
+                Err(_) => println!("Solver panicked"),
+            }
+            println!("\nWatching {} for changes, press Ctrl-C to stop", path.display());
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}